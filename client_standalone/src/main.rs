@@ -4,6 +4,7 @@ mod prelude {
 
 use core_render::render::GameRender;
 use core_server::gamedata::GameData;
+use core_server::gamedata::replay::Replay;
 use core_shared::update::GameUpdate;
 use prelude::*;
 
@@ -54,16 +55,51 @@ fn main() -> Result<(), Error> {
         Pixels::new(width as u32, height as u32, surface_texture)?
     };
 
+    // --record FILE records the session, --replay FILE plays a recorded session back
+    let mut record_path : Option<PathBuf> = None;
+    let mut replay : Option<Replay> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--record" {
+            record_path = args.next().map(|p| PathBuf::from(p));
+        } else
+        if arg == "--replay" {
+            if let Some(path) = args.next() {
+                replay = Replay::load_from_path(&PathBuf::from(&path));
+                if replay.is_none() {
+                    println!("Failed to load the replay {}", path);
+                }
+            }
+        }
+    }
+
     // Init server
     let mut game = GameData::load_from_path(PathBuf::new());
-    game.startup();
-    let player_id = 131313;
+    // When replaying follow the player of the recording
+    let mut player_id = 131313;
+    if let Some(replay) = &replay {
+        if let Some(id) = replay.get_player_id() {
+            player_id = id;
+        }
+    }
 
     let mut game_rect = (0, 0, 0, 0);
 
     // Init renderer
     let mut render = GameRender::new(PathBuf::new(), player_id);
 
+    if let Some(replay) = replay {
+        render.set_seed(replay.seed);
+        game.start_replay(replay);
+    } else {
+        if record_path.is_some() {
+            game.start_recording(Some(player_id));
+        }
+        game.startup();
+    }
+    let mut replay_finished = false;
+
     let mut anim_counter : usize = 0;
     let mut timer : u128 = 0;
     let mut game_tick_timer : u128 = 0;
@@ -81,6 +117,10 @@ fn main() -> Result<(), Error> {
             if curr_time > game_tick_timer + GAME_TICK_IN_MS {
                 // let start = get_time();
                 game.tick();
+                if game.replay_finished() && replay_finished == false {
+                    println!("Replay finished after {} ticks", game.tick_count);
+                    replay_finished = true;
+                }
                 // let stop = get_time();
                 // println!("tick time {:?}", stop - start);
                 //window.request_redraw();
@@ -89,7 +129,7 @@ fn main() -> Result<(), Error> {
             }
 
             // Poll the update and draw it
-            if let Some(update_string) = game.poll_update(131313) {
+            if let Some(update_string) = game.poll_update(player_id) {
                 let update = serde_json::from_str::<GameUpdate>(&update_string).ok();
 
                 if let Some(update) = update {
@@ -215,6 +255,13 @@ fn main() -> Result<(), Error> {
             // Close events
             if /*input.key_pressed(VirtualKeyCode::Escape) ||*/ input.quit() {
                 *control_flow = ControlFlow::Exit;
+                if let Some(path) = &record_path {
+                    if let Some(replay) = game.stop_recording() {
+                        if replay.save_to_path(path) == false {
+                            println!("Failed to write the replay {:?}", path);
+                        }
+                    }
+                }
                 game.shutdown();
                 return;
             }
@@ -1,6 +1,12 @@
 use std::collections::HashMap;
-use core_shared::{regiondata::GameRegionData, light::Light};
-use rand::{thread_rng, Rng};
+use core_shared::{regiondata::GameRegionData, light::Light, clock::get_daylight};
+use rand::Rng;
+
+/// The ambient light of regions without a lighting mode
+const DEFAULT_AMBIENT_LIGHT : f64 = 0.5;
+
+/// The ambient light of outdoor regions at night
+const NIGHT_AMBIENT_LIGHT : f64 = 0.15;
 
 /*
 #[derive(PartialEq, Clone, Debug)]
@@ -9,15 +15,13 @@ pub struct TileLighting {
     pub dynamic                 : f32,
 }*/
 
-pub fn compute_lighting(_region: &GameRegionData, lights: &Vec<Light>) -> HashMap<(isize, isize), f64> {
+pub fn compute_lighting<R: Rng>(_region: &GameRegionData, lights: &Vec<Light>, rng: &mut R) -> HashMap<(isize, isize), f64> {
     let mut map : HashMap<(isize, isize), f64> = HashMap::new();
 
     //for (index, area) in region.areas.iter().enumerate() {
         //for n in region.
     //}
 
-    let mut rng = thread_rng();
-
     for l in lights {
         map.insert(l.position.clone(), 1.0);
 
@@ -53,4 +57,15 @@ pub fn compute_lighting(_region: &GameRegionData, lights: &Vec<Light>) -> HashMa
     }
 
     map
-}
\ No newline at end of file
+}
+
+/// The ambient light of the region at the given time of day (minutes since midnight). Regions with the
+/// "daylight" lighting mode are outdoors and get darker at night.
+pub fn compute_ambient_light(region: &GameRegionData, time: usize) -> f64 {
+    if let Some(property) = region.settings.get("lighting") {
+        if property.as_string() == Some("daylight".to_string()) {
+            return NIGHT_AMBIENT_LIGHT + (1.0 - NIGHT_AMBIENT_LIGHT) * get_daylight(time);
+        }
+    }
+    DEFAULT_AMBIENT_LIGHT
+}
@@ -2,8 +2,9 @@
 use std::{path::PathBuf, collections::{HashMap, HashSet}};
 
 use core_shared::{asset::{Asset, TileUsage}, update::GameUpdate, regiondata::GameRegionData, message::MessageData, light::Light};
-use crate::{draw2d::Draw2D, script_types::*, lighting::compute_lighting};
+use crate::{draw2d::Draw2D, script_types::*, lighting::{ compute_lighting, compute_ambient_light }};
 use rhai::{ Engine, Scope, AST, Dynamic };
+use rand::{rngs::StdRng, SeedableRng};
 
 use core_shared::actions::*;
 
@@ -31,6 +32,9 @@ pub struct GameRender<'a> {
 
     pub messages                : Vec<MessageData>,
 
+    /// The current game state, reconstructed from keyframe and delta updates
+    pub state                   : GameUpdate,
+
     pub last_position           : (usize, isize, isize),
     pub transition_steps        : isize,
     pub transition_counter      : isize,
@@ -38,6 +42,9 @@ pub struct GameRender<'a> {
 
     pub player_id               : usize,
 
+    /// Drives the light flicker, see set_seed() for reproducible frames
+    rng                         : StdRng,
+
     //#[cfg(target_arch = "wasm32")]
     pub audio_engine            : Option<AudioEngine>
 }
@@ -76,7 +83,19 @@ impl GameRender<'_> {
             .register_fn("text", ScriptDraw::text);
 
         engine.register_type_with_name::<ScriptCmd>("Cmd")
-            .register_fn("move", ScriptCmd::cmd_move);
+            .register_fn("move", ScriptCmd::cmd_move)
+            .register_fn("save", ScriptCmd::cmd_save)
+            .register_fn("load", ScriptCmd::cmd_load)
+            .register_fn("take", ScriptCmd::cmd_take)
+            .register_fn("take", ScriptCmd::cmd_take_from)
+            .register_fn("drop", ScriptCmd::cmd_drop)
+            .register_fn("use_item", ScriptCmd::cmd_use_item)
+            .register_fn("talk", ScriptCmd::cmd_talk)
+            .register_fn("choose", ScriptCmd::cmd_choose);
+
+        engine.register_type_with_name::<ScriptItem>("Item")
+            .register_get("name", ScriptItem::get_name)
+            .register_get("tile", ScriptItem::get_tile);
 
         engine.register_type_with_name::<ScriptMessageCmd>("MessageCmd")
             .register_fn("status", ScriptMessageCmd::status);
@@ -111,6 +130,8 @@ impl GameRender<'_> {
 
             messages            : vec![],
 
+            state               : GameUpdate::new(),
+
             last_position       : (100000, 0, 0),
             transition_steps    : 5,
             transition_counter  : 0,
@@ -118,13 +139,23 @@ impl GameRender<'_> {
 
             player_id,
 
+            rng                 : StdRng::from_entropy(),
+
             //#[cfg(target_arch = "wasm32")]
             audio_engine        : None,
         }
     }
 
+    /// Seeds the light flicker so that the rendered frames are reproducible
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
     pub fn process_update(&mut self, update: &GameUpdate) -> Option<(String, Option<usize>)> {
 
+        // Apply the keyframe or delta to the current state
+        self.state.apply(update);
+
         // New screen script ?
         if let Some(screen_script) = &update.screen {
 
@@ -207,8 +238,63 @@ impl GameRender<'_> {
         }
 
         // Insert the lights
-        if let Some(position) = update.position {
-            self.lights.insert(position.0, update.lights.clone());
+        if let Some(position) = self.state.position {
+            self.lights.insert(position.0, self.state.lights.clone());
+        }
+
+        // The inventory for the screen script
+        let mut inventory = rhai::Array::new();
+        for item in &self.state.inventory {
+            let tile = item.tile.unwrap_or((0, 0, 0));
+            inventory.push(Dynamic::from(ScriptItem { name: item.name.clone(), tile: ScriptTile::new(tile) }));
+        }
+        self.scope.set_value("inventory", inventory);
+
+        // The game clock
+        self.scope.set_value("hour", (self.state.time / 60) as i64);
+        self.scope.set_value("minute", (self.state.time % 60) as i64);
+
+        // The global variables, changes made by the screen script are sent back to the server
+        let mut globals = rhai::Map::new();
+        for (name, value) in &self.state.globals {
+            globals.insert(name.as_str().into(), Dynamic::from(*value));
+        }
+        self.scope.set_value("globals", globals);
+
+        // The quests of the player
+        let mut journal = rhai::Array::new();
+        for quest in &self.state.journal {
+            let mut map = rhai::Map::new();
+            map.insert("name".into(), Dynamic::from(quest.name.clone()));
+            map.insert("description".into(), Dynamic::from(quest.description.clone()));
+            map.insert("objective".into(), Dynamic::from(quest.objective.clone()));
+            map.insert("progress".into(), Dynamic::from(quest.progress as i64));
+            map.insert("required".into(), Dynamic::from(quest.required as i64));
+            map.insert("completed".into(), Dynamic::from(quest.completed));
+            journal.push(Dynamic::from(map));
+        }
+        self.scope.set_value("journal", journal);
+
+        // The other members of the party
+        let mut party = rhai::Array::new();
+        for member in &self.state.party {
+            let mut map = rhai::Map::new();
+            map.insert("name".into(), Dynamic::from(member.name.clone()));
+            map.insert("tile".into(), Dynamic::from(ScriptTile::new(member.tile)));
+            party.push(Dynamic::from(map));
+        }
+        self.scope.set_value("party", party);
+
+        // The open conversation, () if there is none
+        if let Some(dialog) = &self.state.dialog {
+            let mut map = rhai::Map::new();
+            map.insert("from".into(), Dynamic::from(dialog.from.clone()));
+            map.insert("text".into(), Dynamic::from(dialog.text.clone()));
+            let choices : rhai::Array = dialog.choices.iter().map(|c| Dynamic::from(c.clone())).collect();
+            map.insert("choices".into(), Dynamic::from(choices));
+            self.scope.set_value("dialog", map);
+        } else {
+            self.scope.set_value("dialog", ());
         }
 
         None
@@ -247,6 +333,10 @@ impl GameRender<'_> {
             }
         }
 
+        // Draw the reconstructed state, not the (possibly delta) update
+        let state = std::mem::replace(&mut self.state, GameUpdate::new());
+        let update = &state;
+
         if let Some(mut draw) = self.scope.get_value::<ScriptDraw>("draw") {
 
             //let game_frame = &mut self.frame[..];
@@ -463,6 +553,7 @@ impl GameRender<'_> {
             self.scope.set_value("draw", draw);
         }
 
+        self.state = state;
         None
     }
 
@@ -491,7 +582,7 @@ impl GameRender<'_> {
             // Compute the light_map
             let mut light_map : HashMap<(isize, isize), f64> = HashMap::new();
             if let Some(lights) = self.lights.get(&region.id) {
-                light_map = compute_lighting(&region, lights);
+                light_map = compute_lighting(&region, lights, &mut self.rng);
             }
 
             // Clear if not in a transition
@@ -520,7 +611,7 @@ impl GameRender<'_> {
                 offset.1 -= top;
             }
 
-            let base_light = 0.5;
+            let base_light = compute_ambient_light(&region, update.time);
 
             // Draw Region
             for y in 0..y_tiles {
@@ -552,6 +643,30 @@ impl GameRender<'_> {
                 }
             }
 
+            // Draw Loot
+            for (position, item) in &update.loot {
+
+                if let Some(tile) = item.tile {
+                    if position.0 >= offset.0 && position.0 < offset.0 + x_tiles && position.1 >= offset.1 && position.1 < offset.1 + y_tiles {
+                        let pos = (rect.0 + left_offset + ((position.0 - offset.0) as usize) * tile_size, rect.1 + top_offset + ((position.1 - offset.1) as usize) * tile_size);
+
+                        if let Some(set) = &set {
+                            if set.contains(&(((pos.0 - rect.0) / self.tile_size) as isize, ((pos.1 - rect.1) / self.tile_size) as isize)) == false {
+                                continue;
+                            }
+                        }
+
+                        let mut light = base_light;
+                        if let Some(l) = light_map.get(position) {
+                            light += *l;
+                        }
+
+                        let map = self.asset.get_map_of_id(tile.0);
+                        self.draw2d.draw_animated_tile_with_blended_color(&mut self.frame[..], &pos, map, stride, &(tile.1, tile.2), anim_counter, tile_size, &background, light);
+                    }
+                }
+            }
+
             // Draw Characters
             for character in &update.characters {
 
@@ -675,32 +790,68 @@ impl GameRender<'_> {
     fn process_cmds(&mut self, player_id: usize) -> Vec<String> {
         let mut commands = vec![];
 
+        if let Some(globals) = self.scope.get_value::<rhai::Map>("globals") {
+            for (name, value) in self.state.globals.iter_mut() {
+                if let Some(v) = globals.get(name.as_str()) {
+                    let v = if let Some(f) = v.as_float().ok() { f } else if let Some(i) = v.as_int().ok() { i as f64 } else { *value };
+                    if v != *value {
+                        *value = v;
+                        if let Some(action) = pack_action(player_id, "global".to_string(), PlayerDirection::None, format!("{}={}", name, v)) {
+                            commands.push(action);
+                        }
+                    }
+                }
+            }
+        }
+
         if let Some(mut cmd) = self.scope.get_value::<ScriptCmd>("cmd") {
 
             for cmd in &cmd.commands {
 
                 match cmd {
                     ScriptServerCmd::Move(direction) => {
-                        let mut dir : Option<PlayerDirection> = None;
-
-                        if direction == "west" {
-                            dir = Some(PlayerDirection::West);
-                        } else
-                        if direction == "north" {
-                            dir = Some(PlayerDirection::North);
-                        } else
-                        if direction == "east" {
-                            dir = Some(PlayerDirection::East);
-                        } else
-                        if direction == "south" {
-                            dir = Some(PlayerDirection::South);
-                        }
-
-                        if let Some(dir) = dir {
+                        if let Some(dir) = get_direction(direction) {
                             if let Some(action) = pack_action(player_id, "onMove".to_string(), dir, "".to_string()) {
                                 commands.push(action);
                             }
                         }
+                    },
+                    ScriptServerCmd::Save(slot) => {
+                        if let Some(action) = pack_action(player_id, "save".to_string(), PlayerDirection::None, slot.clone()) {
+                            commands.push(action);
+                        }
+                    },
+                    ScriptServerCmd::Load(slot) => {
+                        if let Some(action) = pack_action(player_id, "load".to_string(), PlayerDirection::None, slot.clone()) {
+                            commands.push(action);
+                        }
+                    },
+                    ScriptServerCmd::Take(direction) => {
+                        let dir = get_direction(direction).unwrap_or(PlayerDirection::None);
+                        if let Some(action) = pack_action(player_id, "onTake".to_string(), dir, "".to_string()) {
+                            commands.push(action);
+                        }
+                    },
+                    ScriptServerCmd::Drop(item) => {
+                        if let Some(action) = pack_action(player_id, "onDrop".to_string(), PlayerDirection::None, item.clone()) {
+                            commands.push(action);
+                        }
+                    },
+                    ScriptServerCmd::UseItem(item) => {
+                        if let Some(action) = pack_action(player_id, "onUse".to_string(), PlayerDirection::None, item.clone()) {
+                            commands.push(action);
+                        }
+                    },
+                    ScriptServerCmd::Talk(direction) => {
+                        let dir = get_direction(direction).unwrap_or(PlayerDirection::None);
+                        if let Some(action) = pack_action(player_id, "onTalk".to_string(), dir, "".to_string()) {
+                            commands.push(action);
+                        }
+                    },
+                    ScriptServerCmd::Choose(choice) => {
+                        if let Some(action) = pack_action(player_id, "choice".to_string(), PlayerDirection::None, choice.to_string()) {
+                            commands.push(action);
+                        }
                     }
                 }
             }
@@ -810,4 +961,15 @@ impl GameRender<'_> {
 
     }
 
-}
\ No newline at end of file
+}
+
+/// Converts the direction name of a script command
+fn get_direction(direction: &str) -> Option<PlayerDirection> {
+    match direction {
+        "west" => Some(PlayerDirection::West),
+        "north" => Some(PlayerDirection::North),
+        "east" => Some(PlayerDirection::East),
+        "south" => Some(PlayerDirection::South),
+        _ => None,
+    }
+}
@@ -254,6 +254,13 @@ impl ScriptMessageCmd {
 #[derive(PartialEq, Clone, Debug)]
 pub enum ScriptServerCmd {
     Move(String),
+    Save(String),
+    Load(String),
+    Take(String),
+    Drop(String),
+    UseItem(String),
+    Talk(String),
+    Choose(i64),
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -272,7 +279,61 @@ impl ScriptCmd {
         self.commands.push(ScriptServerCmd::Move(direction.to_owned().to_lowercase()));
     }
 
+    pub fn cmd_save(&mut self, slot: &str) {
+        self.commands.push(ScriptServerCmd::Save(slot.to_owned()));
+    }
+
+    pub fn cmd_load(&mut self, slot: &str) {
+        self.commands.push(ScriptServerCmd::Load(slot.to_owned()));
+    }
+
+    /// Take an item from below the player
+    pub fn cmd_take(&mut self) {
+        self.commands.push(ScriptServerCmd::Take("".to_string()));
+    }
+
+    /// Take an item from the tile in the given direction
+    pub fn cmd_take_from(&mut self, direction: &str) {
+        self.commands.push(ScriptServerCmd::Take(direction.to_owned().to_lowercase()));
+    }
+
+    pub fn cmd_drop(&mut self, item: &str) {
+        self.commands.push(ScriptServerCmd::Drop(item.to_owned()));
+    }
+
+    pub fn cmd_use_item(&mut self, item: &str) {
+        self.commands.push(ScriptServerCmd::UseItem(item.to_owned()));
+    }
+
+    /// Talk to the character in the given direction
+    pub fn cmd_talk(&mut self, direction: &str) {
+        self.commands.push(ScriptServerCmd::Talk(direction.to_owned().to_lowercase()));
+    }
+
+    /// Pick a choice of the current dialog, the index into dialog.choices
+    pub fn cmd_choose(&mut self, choice: i64) {
+        self.commands.push(ScriptServerCmd::Choose(choice));
+    }
+
     pub fn clear(&mut self) {
         self.commands.clear();
     }
-}
\ No newline at end of file
+}
+// --- Items
+
+/// An item in the inventory of the player
+#[derive(PartialEq, Debug, Clone)]
+pub struct ScriptItem {
+    pub name            : String,
+    pub tile            : ScriptTile,
+}
+
+impl ScriptItem {
+    pub fn get_name(&mut self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_tile(&mut self) -> ScriptTile {
+        self.tile.clone()
+    }
+}
@@ -0,0 +1,62 @@
+//! Measures the game tick with a growing amount of NPCs, run with "cargo bench -p core_server".
+//! Uses the game project of the repository.
+
+use core_server::gamedata::GameData;
+use core_server::gamedata::behavior::BehaviorInstanceType;
+use core_server::gamedata::nodes_utility::get_movement_cost;
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+const TICKS : u32 = 100;
+
+/// Places the given amount of copies of the first NPC on the walkable tiles of its region and ticks as fast as possible
+fn run_benchmark(count: usize) {
+    let mut game = GameData::load_from_path(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."));
+    game.set_seed(1);
+    game.startup_server();
+
+    let npc = game.instances.iter().find(|instance| instance.instance_type == BehaviorInstanceType::NonPlayerCharacter && instance.position.is_some() && instance.tile.is_some());
+
+    let (behavior_id, region_id) = match npc {
+        Some(npc) => (npc.behavior_id, npc.position.unwrap().0),
+        None => {
+            println!("No character found to benchmark with");
+            return;
+        }
+    };
+
+    let mut tiles : Vec<(isize, isize)> = vec![];
+    if let Some(region) = game.regions.get(&region_id) {
+        tiles = region.data.layer1.keys().cloned().collect();
+    }
+    tiles.sort();
+    tiles.retain(|(x, y)| get_movement_cost((region_id, *x, *y), &game).is_some());
+
+    if tiles.is_empty() {
+        println!("The region has no walkable tiles");
+        return;
+    }
+
+    // Spread the NPCs evenly over the region, stacking them if there are more NPCs than tiles
+    for i in 0..count {
+        let tile = tiles[(i * 7919) % tiles.len()];
+        let index = game.create_behavior_instance(behavior_id);
+        game.instances[index].position = Some((region_id, tile.0, tile.1));
+    }
+
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        game.tick();
+    }
+    let elapsed = start.elapsed();
+
+    println!("{:>6} instances on {} tiles: {:.3}ms per tick", game.instances.len(), tiles.len(), elapsed.as_secs_f64() * 1000.0 / TICKS as f64);
+    game.shutdown();
+}
+
+fn main() {
+    for count in [10, 100, 1000, 5000] {
+        run_benchmark(count);
+    }
+}
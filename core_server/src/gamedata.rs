@@ -4,8 +4,13 @@ pub mod nodes;
 pub mod nodes_utility;
 pub mod nodes_area;
 pub mod nodes_game;
+pub mod nodes_control;
+pub mod nodes_quest;
 pub mod script;
 pub mod game;
+pub mod savegame;
+pub mod replay;
+pub mod spatial;
 
 use core_shared::characterdata::CharacterData;
 use core_shared::light::Light;
@@ -14,26 +19,36 @@ use core_shared::regiondata::GameRegionData;
 use core_shared::update::GameUpdate;
 use core_shared::asset::{ TileUsage, Asset };
 use core_shared::{actions::*};
+use core_shared::codec::Codec;
+use core_shared::item::ItemData;
+use core_shared::clock::{ GameClock, parse_time_of_day };
 
-use rhai::{ Engine, Scope, AST };
+use rhai::{ Dynamic, Engine, Scope, AST };
 
 use std::collections::{HashMap, HashSet};
 use std::fs::metadata;
 
-use crate::gamedata::region::GameRegion;
-use crate::gamedata::behavior::{ BehaviorNodeConnector, BehaviorInstance, GameBehavior, BehaviorNodeType, BehaviorType, BehaviorInstanceState };
+use crate::gamedata::region::{ GameRegion, RegionPortal };
+use crate::gamedata::behavior::{ BehaviorNodeConnector, BehaviorInstance, GameBehavior, BehaviorNodeType, BehaviorType, BehaviorInstanceState, SpawnData, CachedPath, GameEvent };
 
-#[cfg(not(feature = "embed_binaries"))]
 use itertools::Itertools;
 
 use std::path::{self, PathBuf};
 use std::fs;
 
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use std::rc::Rc;
+use std::cell::RefCell;
 
 use self::behavior::BehaviorInstanceType;
 use self::game::Game;
-use self::nodes_utility::get_node_value;
+use self::savegame::GameSnapshot;
+use self::replay::{Replay, ReplayEvent, ReplayEventType};
+use self::nodes_utility::{ get_node_value, is_adjacent, get_party_members };
+use self::script::{ GlobalVariables, Factions, parse_reputations };
+use self::nodes_quest::{ QuestDefinition, parse_quest };
 
 #[cfg(feature = "embed_binaries")]
 use core_embed_binaries::Embedded;
@@ -103,10 +118,52 @@ pub struct GameData<'a> {
     // The character instances from the previous tick, used to figure out onEnter, onLeave etc events
     pub prev_area_characters    : HashMap<(usize, usize), Vec<usize>>,
 
+    /// The items lying in the regions, keyed by position
+    pub loot                    : HashMap<(usize, isize, isize), Vec<ItemData>>,
+
+    /// The state of the Spawn area nodes, keyed by (region id, area index, node id)
+    pub spawns                  : HashMap<(usize, usize, usize), SpawnData>,
+    /// The events emitted during the current tick, delivered on the next one
+    pub events                  : Vec<GameEvent>,
+
+    /// The cached walking paths of the instances, keyed by instance index
+    pub paths                   : HashMap<usize, CachedPath>,
+
+    /// The teleport areas leading out of each region, keyed by region id
+    pub portals                 : HashMap<usize, Vec<RegionPortal>>,
+
     // These are fields which provide feedback to the editor / game while running
     pub messages                : Vec<(String, MessageType)>,
     pub executed_connections    : Vec<(BehaviorType, usize, BehaviorNodeConnector)>,
     pub changed_variables       : Vec<(usize, usize, usize, f64)>, // A variable has been changed: instance index, behavior id, node id, new value
+
+    /// Send delta updates to the players instead of full updates every tick, used by network servers
+    pub delta_updates           : bool,
+    /// The maximum amount of delta updates between two keyframes
+    pub keyframe_interval       : usize,
+
+    /// The random number generator of the simulation, shared with the dice resolver of the script engine
+    pub rng                     : Rc<RefCell<ChaCha8Rng>>,
+    /// The seed of the rng when running in deterministic mode, see set_seed()
+    pub seed                    : Option<u64>,
+    /// The amount of ticks since the game started
+    pub tick_count              : usize,
+    /// The in-game clock, configured in the game settings
+    pub clock                   : GameClock,
+    /// The global number variables, shared by the scopes of all instances
+    pub globals                 : GlobalVariables,
+
+    /// The reputations between the factions
+    pub factions                : Factions,
+    /// The quests of the Quest nodes of the game behavior, parsed on startup
+    pub quests                  : Vec<QuestDefinition>,
+    /// The scope of the expressions and scripts of area behaviors
+    pub area_scope              : Scope<'a>,
+
+    /// The session being recorded, see start_recording()
+    pub recording               : Option<Replay>,
+    /// The session being replayed and the index of its next event, see start_replay()
+    pub replay                  : Option<(Replay, usize)>,
 }
 
 impl GameData<'_> {
@@ -195,8 +252,12 @@ impl GameData<'_> {
             let behavior_path = path.join("game").join("characters");
             if let Some(paths) = fs::read_dir(behavior_path.clone()).ok() {
 
+                // Sort the paths to get a stable load and execution order
+                let mut paths: Vec<_> = paths.filter_map(|r| r.ok()).collect();
+                paths.sort_by_key(|dir| dir.path());
+
                 for path in paths {
-                    let path = &path.unwrap().path();
+                    let path = &path.path();
                     let md = metadata(path).unwrap();
 
                     if md.is_file() {
@@ -275,8 +336,11 @@ impl GameData<'_> {
             let systems_path = path.join("game").join("systems");
             if let Some(paths) = fs::read_dir(systems_path.clone()).ok() {
 
+                let mut paths: Vec<_> = paths.filter_map(|r| r.ok()).collect();
+                paths.sort_by_key(|dir| dir.path());
+
                 for path in paths {
-                    let path = &path.unwrap().path();
+                    let path = &path.path();
                     let md = metadata(path).unwrap();
 
                     if md.is_file() {
@@ -338,8 +402,11 @@ impl GameData<'_> {
         let item_path = path.join("game").join("items");
         if let Some(paths) = fs::read_dir(item_path.clone()).ok() {
 
+            let mut paths: Vec<_> = paths.filter_map(|r| r.ok()).collect();
+            paths.sort_by_key(|dir| dir.path());
+
             for path in paths {
-                let path = &path.unwrap().path();
+                let path = &path.path();
                 let md = metadata(path).unwrap();
 
                 if md.is_file() {
@@ -404,6 +471,27 @@ impl GameData<'_> {
         nodes.insert(BehaviorNodeType::Message, nodes::message);
         nodes.insert(BehaviorNodeType::Pathfinder, nodes::pathfinder);
         nodes.insert(BehaviorNodeType::Lookout, nodes::lookout);
+        nodes.insert(BehaviorNodeType::TimeOfDay, nodes::time_of_day);
+        nodes.insert(BehaviorNodeType::Schedule, nodes::schedule);
+        nodes.insert(BehaviorNodeType::Selector, nodes_control::selector);
+        nodes.insert(BehaviorNodeType::Inverter, nodes_control::inverter);
+        nodes.insert(BehaviorNodeType::Repeat, nodes_control::repeat);
+        nodes.insert(BehaviorNodeType::Parallel, nodes_control::parallel);
+        nodes.insert(BehaviorNodeType::Cooldown, nodes_control::cooldown);
+        nodes.insert(BehaviorNodeType::Random, nodes_control::random);
+        nodes.insert(BehaviorNodeType::EmitEvent, nodes::emit_event);
+        nodes.insert(BehaviorNodeType::Talk, nodes::player_talk);
+        nodes.insert(BehaviorNodeType::Dialog, nodes::dialog);
+        nodes.insert(BehaviorNodeType::StartQuest, nodes_quest::start_quest);
+        nodes.insert(BehaviorNodeType::AdvanceQuest, nodes_quest::advance_quest);
+        nodes.insert(BehaviorNodeType::CompleteQuest, nodes_quest::complete_quest);
+        nodes.insert(BehaviorNodeType::AdjustReputation, nodes::adjust_reputation);
+        nodes.insert(BehaviorNodeType::Recruit, nodes::recruit);
+        nodes.insert(BehaviorNodeType::Dismiss, nodes::dismiss);
+        nodes.insert(BehaviorNodeType::Follow, nodes::follow);
+        nodes.insert(BehaviorNodeType::Wander, nodes::wander);
+        nodes.insert(BehaviorNodeType::Flee, nodes::flee);
+        nodes.insert(BehaviorNodeType::Patrol, nodes::patrol);
         nodes.insert(BehaviorNodeType::CloseIn, nodes::close_in);
         nodes.insert(BehaviorNodeType::CallSystem, nodes::call_system);
         nodes.insert(BehaviorNodeType::CallBehavior, nodes::call_behavior);
@@ -420,23 +508,29 @@ impl GameData<'_> {
         nodes.insert(BehaviorNodeType::MessageArea, nodes_area::message_area);
         nodes.insert(BehaviorNodeType::AudioArea, nodes_area::audio_area);
         nodes.insert(BehaviorNodeType::LightArea, nodes_area::light_area);
+        nodes.insert(BehaviorNodeType::Spawn, nodes_area::spawn_area);
 
         nodes.insert(BehaviorNodeType::Move, nodes::player_move);
+        nodes.insert(BehaviorNodeType::Take, nodes::player_take);
+        nodes.insert(BehaviorNodeType::Drop, nodes::player_drop);
+        nodes.insert(BehaviorNodeType::UseItem, nodes::player_use_item);
 
         nodes.insert(BehaviorNodeType::Screen, nodes_game::screen);
 
         let mut engine = Engine::new();
 
+        let rng = Rc::new(RefCell::new(ChaCha8Rng::from_entropy()));
+
         // Variable resolver for d??? -> random(???)
+        let dice_rng = rng.clone();
         #[allow(deprecated)]
-        engine.on_var(|name, _index, _context| {
+        engine.on_var(move |name, _index, _context| {
 
             if name.starts_with("d") {
                 let mut s = name.to_string();
                 s.remove(0);
                 if let Some(n) = s.parse::<i64>().ok() {
-                    let mut rng = thread_rng();
-                    let random = rng.gen_range(1..=n) as f64;
+                    let random = dice_rng.borrow_mut().gen_range(1..=n) as f64;
                     //println!{"d{} {}",n, random};
                     return Ok(Some(random.into()));
                 }
@@ -448,6 +542,9 @@ impl GameData<'_> {
         use pathfinding::num_traits::ToPrimitive;
         engine.register_fn("to_string", |x: f64| format!("{}", x.to_isize().unwrap()));
 
+        GlobalVariables::register(&mut engine);
+        Factions::register(&mut engine);
+
         Self {
 
             path                    : path.clone(),
@@ -493,10 +590,30 @@ impl GameData<'_> {
             lights                  : HashMap::new(),
             area_characters         : HashMap::new(),
             prev_area_characters    : HashMap::new(),
+            loot                    : HashMap::new(),
+            spawns                  : HashMap::new(),
+            events                  : vec![],
+            paths                   : HashMap::new(),
+            portals                 : HashMap::new(),
 
             messages                : vec![],
             executed_connections    : vec![],
             changed_variables       : vec![],
+
+            delta_updates           : false,
+            keyframe_interval       : 20,
+
+            rng,
+            seed                    : None,
+            tick_count              : 0,
+            clock                   : GameClock::new(),
+            globals                 : GlobalVariables::new(),
+            factions                : Factions::new(),
+            quests                  : vec![],
+            area_scope              : Scope::new(),
+
+            recording               : None,
+            replay                  : None,
         }
     }
 
@@ -578,10 +695,30 @@ impl GameData<'_> {
             lights                  : HashMap::new(),
             area_characters         : HashMap::new(),
             prev_area_characters    : HashMap::new(),
+            loot                    : HashMap::new(),
+            spawns                  : HashMap::new(),
+            events                  : vec![],
+            paths                   : HashMap::new(),
+            portals                 : HashMap::new(),
 
             messages                : vec![],
             executed_connections    : vec![],
             changed_variables       : vec![],
+
+            delta_updates           : false,
+            keyframe_interval       : 20,
+
+            rng                     : Rc::new(RefCell::new(ChaCha8Rng::from_entropy())),
+            seed                    : None,
+            tick_count              : 0,
+            clock                   : GameClock::new(),
+            globals                 : GlobalVariables::new(),
+            factions                : Factions::new(),
+            quests                  : vec![],
+            area_scope              : Scope::new(),
+
+            recording               : None,
+            replay                  : None,
         }
     }
 
@@ -671,6 +808,25 @@ impl GameData<'_> {
         self.systems.insert(system.data.id, system);
     }
 
+    #[cfg(feature = "data_editing")]
+    /// Create a new item
+    pub fn create_item_behavior(&mut self, name: String, _behavior_type: usize) {
+
+        let path = self.path.join("game").join("items").join(name.clone() + ".json");
+
+        let mut item = GameBehavior::load_from_path(&path, &self.path.join("game").join("items"));
+        item.data.name = name.clone();
+
+        self.items_names.push(item.name.clone());
+        self.items_ids.push(item.data.id);
+
+        item.add_node(BehaviorNodeType::BehaviorType, "Behavior Type".to_string());
+        item.add_node(BehaviorNodeType::BehaviorTree, "onUse".to_string());
+        item.save_data();
+
+        self.items.insert(item.data.id, item);
+    }
+
     #[cfg(feature = "data_editing")]
     /// Sets the value for the given behavior id
     pub fn set_behavior_id_value(&mut self, id: (usize, usize, String), value: (f64, f64, f64, f64, String), behavior_type: BehaviorType) {
@@ -736,14 +892,205 @@ impl GameData<'_> {
         None
     }
 
-    /// Save data and return it
-    pub fn save(&self) -> String {
-        let json = serde_json::to_string(&self.instances).unwrap();
+    /// Save the state of the running game and return it
+    pub fn save(&mut self) -> String {
+
+        // Store the number and string variables of the scopes in the instances
+        for index in 0..self.instances.len() {
+            let mut number_values = HashMap::new();
+            let mut string_values = HashMap::new();
+            if let Some(scope) = self.scopes.get(index) {
+                for (name, _constant, value) in scope.iter() {
+                    if let Some(value) = value.as_float().ok() {
+                        number_values.insert(name.to_string(), value);
+                    } else
+                    if let Some(value) = value.into_string().ok() {
+                        string_values.insert(name.to_string(), value);
+                    }
+                }
+            }
+            self.instances[index].number_values = number_values;
+            self.instances[index].string_values = string_values;
+        }
+
+        let mut displacements = vec![];
+        for id in &self.regions_ids {
+            if let Some(region) = self.regions.get(id) {
+                displacements.push((*id, region.displacements.iter().sorted_by_key(|(k, _)| **k).map(|(k, v)| (*k, v.clone())).collect()));
+            }
+        }
+
+        let snapshot = GameSnapshot {
+            instances               : std::mem::take(&mut self.instances),
+            player_ids_inst_indices : self.player_ids_inst_indices.clone(),
+            game_instance_index     : self.game_instance_index,
+            displacements,
+            seed                    : self.seed,
+            rng_word_pos            : self.rng.borrow().get_word_pos(),
+            tick_count              : self.tick_count,
+            loot                    : self.loot.iter().sorted_by_key(|(pos, _)| **pos).map(|(pos, items)| (*pos, items.clone())).collect(),
+            spawns                  : self.spawns.iter().sorted_by_key(|(key, _)| **key).map(|(key, spawn)| (*key, spawn.clone())).collect(),
+            globals                 : self.globals.to_vec(),
+            factions                : self.factions.to_vec(),
+            events                  : self.events.clone(),
+        };
+
+        let json = serde_json::to_string(&snapshot).unwrap_or("".to_string());
+        self.instances = snapshot.instances;
         json
     }
 
+    /// Restores a game state created by save(), returns false if the data is invalid
+    pub fn load(&mut self, json: &String) -> bool {
+
+        if let Some(snapshot) = serde_json::from_str::<GameSnapshot>(json).ok() {
+
+            if self.asset.is_none() {
+                self.asset = Some(Asset::new());
+                #[cfg(not(feature = "embed_binaries"))]
+                self.asset.as_mut().unwrap().load_from_path(self.path.clone());
+                #[cfg(feature = "embed_binaries")]
+                self.asset.as_mut().unwrap().load_from_embedded();
+            }
+
+            self.create_region_graph();
+            self.read_clock_settings();
+            self.clear_instances();
+
+            self.create_globals();
+            for (name, value) in &snapshot.globals {
+                self.globals.define(name, *value);
+            }
+
+            self.create_factions();
+            self.create_quests();
+            for (a, b, value) in &snapshot.factions {
+                self.factions.set(a, b, *value);
+            }
+
+            for instance in snapshot.instances {
+                let mut scope = Scope::new();
+                for (name, value) in &instance.number_values {
+                    scope.push(name.clone(), *value);
+                }
+                for (name, value) in &instance.string_values {
+                    scope.push(name.clone(), value.clone());
+                }
+                scope.push("globals", self.globals.clone());
+                scope.push("factions", self.factions.clone());
+
+                self.instances.push(instance);
+                self.scopes.push(scope);
+            }
+
+            self.player_ids_inst_indices = snapshot.player_ids_inst_indices;
+            self.game_instance_index = snapshot.game_instance_index;
+
+            for (id, displacements) in snapshot.displacements {
+                if let Some(region) = self.regions.get_mut(&id) {
+                    region.displacements = displacements.into_iter().collect();
+                }
+            }
+
+            self.area_characters = HashMap::new();
+            self.prev_area_characters = HashMap::new();
+
+            // Continue the random sequence where the save left off
+            if let Some(seed) = snapshot.seed {
+                self.set_seed(seed);
+                self.rng.borrow_mut().set_word_pos(snapshot.rng_word_pos);
+            }
+            self.tick_count = snapshot.tick_count;
+            self.loot = snapshot.loot.into_iter().collect();
+            self.spawns = snapshot.spawns.into_iter().collect();
+            self.events = snapshot.events;
+
+            return true;
+        }
+        false
+    }
+
+    /// The path of the given save slot
+    fn get_slot_path(&self, slot: &String) -> Option<PathBuf> {
+        let slot = slot.trim();
+        if slot.is_empty() || slot.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == ' ') == false {
+            println!("Invalid save slot name {}", slot);
+            return None;
+        }
+        Some(self.path.join("game").join("saves").join(format!("{}.json", slot)))
+    }
+
+    /// Save the game into the given slot
+    pub fn save_to_slot(&mut self, slot: &String) -> bool {
+        if let Some(path) = self.get_slot_path(slot) {
+            let json = self.save();
+            if let Some(dir) = path.parent() {
+                _ = fs::create_dir_all(dir);
+            }
+            if fs::write(&path, json).is_ok() {
+                self.messages.push((format!("Game saved to slot {}", slot), MessageType::Status));
+                return true;
+            }
+            println!("Failed to write save slot {:?}", path);
+        }
+        false
+    }
+
+    /// Load the game from the given slot. Refused while recording or replaying, the snapshot would replace the
+    /// tick count and random state the recorded ticks are based on.
+    pub fn load_from_slot(&mut self, slot: &String) -> bool {
+        if self.recording.is_some() || self.replay.is_some() {
+            self.messages.push(("Loading is not possible while a session is recorded or replayed".to_string(), MessageType::Error));
+            return false;
+        }
+        if let Some(path) = self.get_slot_path(slot) {
+            if let Some(json) = fs::read_to_string(&path).ok() {
+                if self.load(&json) {
+                    self.messages.push((format!("Game loaded from slot {}", slot), MessageType::Status));
+                    return true;
+                }
+            }
+            println!("Failed to load save slot {:?}", path);
+        }
+        false
+    }
+
     /// Create a new behavior instance for the given id and return it's instance index
     pub fn create_behavior_instance(&mut self, id: usize) -> usize {
+        if let Some((instance, scope)) = self.new_behavior_instance(id) {
+            let index = self.instances.len();
+            self.instances.push(instance);
+            self.scopes.push(scope);
+            return index;
+        }
+        0
+    }
+
+    /// Replaces the dead instance at the given index with a new instance of the behavior and returns the index.
+    /// Used by respawns, references of other instances to the dead one are cleared.
+    pub fn recreate_behavior_instance(&mut self, id: usize, index: usize) -> usize {
+        if let Some((instance, scope)) = self.new_behavior_instance(id) {
+            if let Some(old) = self.instances[index].position {
+                if let Some(region) = self.regions.get_mut(&old.0) {
+                    region.grid.remove(index, (old.1, old.2));
+                }
+            }
+            self.paths.remove(&index);
+            for other in &mut self.instances {
+                if other.target_instance_index == Some(index) {
+                    other.target_instance_index = None;
+                }
+                other.party.retain(|member| *member != index);
+            }
+
+            self.instances[index] = instance;
+            self.scopes[index] = scope;
+        }
+        index
+    }
+
+    /// Creates the instance of the given behavior and its scope
+    fn new_behavior_instance(&mut self, id: usize) -> Option<(BehaviorInstance, Scope<'static>)> {
 
         let mut to_execute : Vec<usize> = vec![];
 
@@ -751,6 +1098,8 @@ impl GameData<'_> {
         let mut tile     : Option<(usize, usize, usize)> = None;
 
         let mut scope = Scope::new();
+        scope.push("globals", self.globals.clone());
+        scope.push("factions", self.factions.clone());
 
         // Insert Dices
         /*
@@ -763,9 +1112,11 @@ impl GameData<'_> {
         scope.push("Value1", 0.0_f64);
         scope.push("Value2", 0.0_f64);
         scope.push("Value3", 0.0_f64);
+        scope.push("faction", "".to_string());
 
         if let Some(behavior) = self.behaviors.get_mut(&id) {
-            for (id, node) in &behavior.data.nodes {
+            // Sorted by node id to get a stable tree execution order
+            for (id, node) in behavior.data.nodes.iter().sorted_by_key(|(id, _)| **id) {
                 if node.behavior_type == BehaviorNodeType::BehaviorTree {
 
                     for c in &behavior.data.connections {
@@ -781,6 +1132,9 @@ impl GameData<'_> {
                     if let Some(value )= node.values.get(&"tile".to_string()) {
                         tile = Some((value.0 as usize, value.1 as usize, value.2 as usize));
                     }
+                    if let Some(value )= node.values.get(&"faction".to_string()) {
+                        scope.set_value("faction", value.4.trim().to_string());
+                    }
                 } else
                 if node.behavior_type == BehaviorNodeType::VariableNumber {
                     if let Some(value )= node.values.get(&"value".to_string()) {
@@ -791,9 +1145,7 @@ impl GameData<'_> {
                 }
             }
 
-            let index = self.instances.len();
-
-            let mut instance = BehaviorInstance {id: thread_rng().gen_range(1..=u32::MAX) as usize, state: BehaviorInstanceState::Normal, name: behavior.name.clone(), behavior_id: id, tree_ids: to_execute.clone(), position, tile, target_instance_index: None, locked_tree: None, party: vec![], node_values: HashMap::new(), state_values: HashMap::new(), number_values: HashMap::new(), sleep_cycles: 0, systems_id: 0, action: None, instance_type: behavior::BehaviorInstanceType::NonPlayerCharacter, update: None, regions_send: HashSet::new(), curr_player_screen_id: None, game_locked_tree: None, curr_player_screen: "".to_string(), messages: vec![], audio: vec![] };
+            let mut instance = BehaviorInstance {id: self.rng.borrow_mut().gen_range(1..=u32::MAX) as usize, state: BehaviorInstanceState::Normal, name: behavior.name.clone(), behavior_id: id, tree_ids: to_execute.clone(), position, tile, target_instance_index: None, locked_tree: None, party: vec![], node_values: HashMap::new(), state_values: HashMap::new(), number_values: HashMap::new(), string_values: HashMap::new(), sleep_cycles: 0, suspended_ticks: 0, systems_id: 0, action: None, dialog: None, instance_type: behavior::BehaviorInstanceType::NonPlayerCharacter, update: None, regions_send: HashSet::new(), curr_player_screen_id: None, game_locked_tree: None, curr_player_screen: "".to_string(), messages: vec![], audio: vec![], last_update: None, updates_since_keyframe: 0, inventory: vec![], facing: PlayerDirection::None };
 
             // Make sure id is unique
             let mut has_id_already = true;
@@ -810,13 +1162,10 @@ impl GameData<'_> {
                 }
             }
 
-            self.instances.push(instance);
-            self.scopes.push(scope);
-
-            return index;
+            return Some((instance, scope));
         }
 
-        0
+        None
     }
 
     /// Create the game instance and return it's instance index
@@ -828,27 +1177,27 @@ impl GameData<'_> {
         let mut locked_tree  : Option<usize> = None;
 
         let mut scope = Scope::new();
+        scope.push("globals", self.globals.clone());
+        scope.push("factions", self.factions.clone());
+
+        // The variables of the game behavior are the globals, the game scripts also see them under their own names
+        for (name, value) in self.globals.to_vec() {
+            scope.push(name, value);
+        }
 
         let behavior = &mut self.game.behavior;
 
-        // Collect name of the startup tree and the variables
+        // Collect name of the startup tree
         for (_id, node) in &behavior.data.nodes {
             if node.behavior_type == BehaviorNodeType::BehaviorType {
                 if let Some(value )= node.values.get(&"startup".to_string()) {
                     startup_name = Some(value.4.clone());
                 }
-            } else
-            if node.behavior_type == BehaviorNodeType::VariableNumber {
-                if let Some(value )= node.values.get(&"value".to_string()) {
-                    scope.push(node.name.clone(), value.0.clone());
-                } else {
-                    scope.push(node.name.clone(), 0.0_f64);
-                }
             }
         }
 
         // Second pass parse the trees and find the startup tree
-        for (id, node) in &behavior.data.nodes {
+        for (id, node) in behavior.data.nodes.iter().sorted_by_key(|(id, _)| **id) {
             if node.behavior_type == BehaviorNodeType::BehaviorTree {
 
                 for c in &behavior.data.connections {
@@ -866,7 +1215,7 @@ impl GameData<'_> {
 
         let index = self.instances.len();
 
-        let mut instance = BehaviorInstance {id: thread_rng().gen_range(1..=u32::MAX) as usize, state: BehaviorInstanceState::Normal, name: behavior.name.clone(), behavior_id: behavior.data.id, tree_ids: to_execute.clone(), position: None, tile: None, target_instance_index: None, locked_tree, party: vec![], node_values: HashMap::new(), state_values: HashMap::new(), number_values: HashMap::new(), sleep_cycles: 0, systems_id: 0, action: None, instance_type: behavior::BehaviorInstanceType::GameLogic, update: None, regions_send: HashSet::new(), curr_player_screen_id: None, game_locked_tree: None, curr_player_screen: "".to_string(), messages: vec![], audio: vec![]};
+        let mut instance = BehaviorInstance {id: self.rng.borrow_mut().gen_range(1..=u32::MAX) as usize, state: BehaviorInstanceState::Normal, name: behavior.name.clone(), behavior_id: behavior.data.id, tree_ids: to_execute.clone(), position: None, tile: None, target_instance_index: None, locked_tree, party: vec![], node_values: HashMap::new(), state_values: HashMap::new(), number_values: HashMap::new(), string_values: HashMap::new(), sleep_cycles: 0, suspended_ticks: 0, systems_id: 0, action: None, dialog: None, instance_type: behavior::BehaviorInstanceType::GameLogic, update: None, regions_send: HashSet::new(), curr_player_screen_id: None, game_locked_tree: None, curr_player_screen: "".to_string(), messages: vec![], audio: vec![], last_update: None, updates_since_keyframe: 0, inventory: vec![], facing: PlayerDirection::None };
 
         // Make sure id is unique
         let mut has_id_already = true;
@@ -891,7 +1240,7 @@ impl GameData<'_> {
 
             if let Some(value)= get_node_value((self.instances[index].behavior_id, *tree_id, "execute"), self, BehaviorType::GameLogic, 0) {
                 if value.0 == 1.0 {
-                    self.execute_game_node(index, tree_id.clone());
+                    self.execute_game_tree(index, tree_id.clone());
                 }
             }
         }
@@ -1101,6 +1450,84 @@ impl GameData<'_> {
         rc
     }
 
+    /// Executes the given node of the item behavior for the instance using the item and follows the connection chain
+    fn execute_item_node(&mut self, instance_index: usize, item_id: usize, node_id: usize) -> Option<BehaviorNodeConnector> {
+
+        let mut connectors : Vec<BehaviorNodeConnector> = vec![];
+        let mut connected_node_ids : Vec<usize> = vec![];
+        let mut possibly_executed_connections : Vec<(BehaviorType, usize, BehaviorNodeConnector)> = vec![];
+
+        let mut is_sequence = false;
+        let mut rc : Option<BehaviorNodeConnector> = None;
+
+        // Call the node and get the resulting BehaviorNodeConnector
+        if let Some(item) = self.items.get_mut(&item_id) {
+            if let Some(node) = item.data.nodes.get_mut(&node_id) {
+
+                // Handle special nodes
+                if node.behavior_type == BehaviorNodeType::BehaviorTree || node.behavior_type == BehaviorNodeType::Linear {
+                    connectors.push(BehaviorNodeConnector::Bottom1);
+                    connectors.push(BehaviorNodeConnector::Bottom2);
+                    connectors.push(BehaviorNodeConnector::Bottom);
+                    connectors.push(BehaviorNodeConnector::Bottom3);
+                    connectors.push(BehaviorNodeConnector::Bottom4);
+                } else
+                if node.behavior_type == BehaviorNodeType::Sequence {
+                    connectors.push(BehaviorNodeConnector::Bottom1);
+                    connectors.push(BehaviorNodeConnector::Bottom2);
+                    connectors.push(BehaviorNodeConnector::Bottom);
+                    connectors.push(BehaviorNodeConnector::Bottom3);
+                    connectors.push(BehaviorNodeConnector::Bottom4);
+                    is_sequence = true;
+                } else {
+                    if let Some(node_call) = self.nodes.get_mut(&node.behavior_type) {
+                        let connector = node_call(instance_index, (item_id, node_id), self, BehaviorType::Items);
+                        rc = Some(connector);
+                        connectors.push(connector);
+                    } else {
+                        connectors.push(BehaviorNodeConnector::Bottom);
+                    }
+                }
+            }
+        }
+
+        // Search the connections to check if we can find an ongoing node connection
+        for connector in connectors {
+            if let Some(item) = self.items.get_mut(&item_id) {
+
+                for c in &item.data.connections {
+                    if c.0 == node_id && c.1 == connector {
+                        connected_node_ids.push(c.2);
+                        if is_sequence == false {
+                            self.executed_connections.push((BehaviorType::Items, c.0, c.1));
+                        } else {
+                            possibly_executed_connections.push((BehaviorType::Items, c.0, c.1));
+                        }
+                    }
+                }
+            }
+        }
+
+        // And if yes execute it
+        for (index, connected_node_id) in connected_node_ids.iter().enumerate() {
+
+            // If this is a sequence, mark this connection as executed
+            if is_sequence {
+                self.executed_connections.push(possibly_executed_connections[index]);
+            }
+
+            if let Some(connector) = self.execute_item_node(instance_index, item_id, *connected_node_id) {
+                if is_sequence {
+                    // Inside a sequence break out if the connector is not Success
+                    if connector == BehaviorNodeConnector::Fail || connector == BehaviorNodeConnector::Right {
+                        break;
+                    }
+                }
+            }
+        }
+        rc
+    }
+
     /// Executes the given node and follows the connection chain
     fn execute_area_node(&mut self, region_id: usize, area_index: usize, node_id: usize) -> Option<BehaviorNodeConnector> {
 
@@ -1142,6 +1569,60 @@ impl GameData<'_> {
         rc
     }
 
+    /// Executes a child node of a control node with the execution function of the behavior type
+    fn execute_child_node(&mut self, behavior_type: BehaviorType, instance_index: usize, behavior_id: usize, node_id: usize) -> Option<BehaviorNodeConnector> {
+        match behavior_type {
+            BehaviorType::Behaviors => self.execute_node(instance_index, node_id),
+            BehaviorType::Systems => self.execute_systems_node(instance_index, node_id),
+            BehaviorType::Items => self.execute_item_node(instance_index, behavior_id, node_id),
+            BehaviorType::GameLogic => self.execute_game_node(instance_index, node_id),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of the given node
+    fn get_node_type(&self, behavior_type: BehaviorType, behavior_id: usize, node_id: usize) -> Option<BehaviorNodeType> {
+        if let Some(behavior) = self.get_behavior(behavior_id, behavior_type) {
+            if let Some(node) = behavior.data.nodes.get(&node_id) {
+                return Some(node.behavior_type);
+            }
+        }
+        None
+    }
+
+    /// Returns the ids of the nodes connected to the given connector of the node
+    fn get_connected_nodes(&self, behavior_type: BehaviorType, behavior_id: usize, node_id: usize, connector: BehaviorNodeConnector) -> Vec<usize> {
+        let mut rc = vec![];
+        if let Some(behavior) = self.get_behavior(behavior_id, behavior_type) {
+            for c in &behavior.data.connections {
+                if c.0 == node_id && c.1 == connector {
+                    rc.push(c.2);
+                }
+            }
+        }
+        rc
+    }
+
+    /// Executes a tree of the game behavior. The game scope holds the globals also under their own names, these
+    /// are updated before the execution and values written to them are copied back to the globals afterwards.
+    fn execute_game_tree(&mut self, instance_index: usize, tree_id: usize) {
+        let globals = self.globals.to_vec();
+        for (name, value) in &globals {
+            self.scopes[instance_index].set_value(name.clone(), *value);
+        }
+
+        self.execute_game_node(instance_index, tree_id);
+
+        for (name, value) in &globals {
+            if let Some(v) = self.scopes[instance_index].get_value::<Dynamic>(name) {
+                let v = if let Some(f) = v.as_float().ok() { f } else if let Some(i) = v.as_int().ok() { i as f64 } else { *value };
+                if v != *value {
+                    self.globals.set(name, v);
+                }
+            }
+        }
+    }
+
     /// Executes the given node and follows the connection chain
     fn execute_game_node(&mut self, instance_index: usize, node_id: usize) -> Option<BehaviorNodeConnector> {
 
@@ -1285,18 +1766,100 @@ impl GameData<'_> {
     // Instance Handling
 
     /// Creates all behavior instances
+    /// Behaviors which are placed in a region only get their placed instances, all others get one instance at their default position.
     pub fn create_behavior_instances(&mut self) {
+        let mut placements = vec![];
+        for region_id in &self.regions_ids {
+            if let Some(region) = self.regions.get(region_id) {
+                for placement in &region.data.instances {
+                    placements.push((*region_id, placement.clone()));
+                }
+            }
+        }
+
         for index in 0..self.behaviors_ids.len() {
             if self.behaviors_names[index] != "Player" {
-                self.create_behavior_instance(self.behaviors_ids[index]);
+                if placements.iter().any(|(_, placement)| placement.behavior == self.behaviors_ids[index]) == false {
+                    self.create_behavior_instance(self.behaviors_ids[index]);
+                }
             } else {
                 self.player_behavior_id = self.behaviors_ids[index];
             }
         }
+
+        for (region_id, placement) in placements {
+            if self.behaviors.contains_key(&placement.behavior) == false || placement.behavior == self.player_behavior_id {
+                continue;
+            }
+
+            let index = self.create_behavior_instance(placement.behavior);
+            self.instances[index].position = Some((region_id, placement.position.0, placement.position.1));
+            if let Some(name) = placement.name {
+                self.instances[index].name = name;
+            }
+            for (name, value) in placement.number_values {
+                if self.scopes[index].contains(&name) {
+                    self.scopes[index].set_value(name, value);
+                }
+            }
+            if let Some(faction) = placement.faction {
+                self.scopes[index].set_value("faction", faction.trim().to_string());
+            }
+        }
+    }
+
+    /// Places the items which have a position in their behavior type node into the regions
+    pub fn create_item_instances(&mut self) {
+        for index in 0..self.items_ids.len() {
+            let mut position : Option<(usize, isize, isize)> = None;
+            if let Some(item) = self.items.get(&self.items_ids[index]) {
+                for (_id, node) in item.data.nodes.iter().sorted_by_key(|(id, _)| **id) {
+                    if node.behavior_type == BehaviorNodeType::BehaviorType {
+                        if let Some(value) = node.values.get(&"position".to_string()) {
+                            if value.0 >= 0.0 {
+                                position = Some((value.0 as usize, value.1 as isize, value.2 as isize));
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(position) = position {
+                if let Some(item) = self.create_item(self.items_ids[index]) {
+                    self.loot.entry(position).or_insert(vec![]).push(item);
+                }
+            }
+        }
+    }
+
+    /// Creates a new item instance of the given item behavior
+    pub fn create_item(&mut self, item_id: usize) -> Option<ItemData> {
+        if let Some(item) = self.items.get(&item_id) {
+            let mut tile = None;
+            for (_id, node) in item.data.nodes.iter().sorted_by_key(|(id, _)| **id) {
+                if node.behavior_type == BehaviorNodeType::BehaviorType {
+                    if let Some(value) = node.values.get(&"tile".to_string()) {
+                        if value.0 >= 0.0 {
+                            tile = Some((value.0 as usize, value.1 as usize, value.2 as usize));
+                        }
+                    }
+                }
+            }
+
+            return Some(ItemData {
+                id          : self.rng.borrow_mut().gen_range(1..=u32::MAX) as usize,
+                item_id,
+                name        : item.name.clone(),
+                tile,
+            });
+        }
+        None
     }
 
     /// Game tick
     pub fn tick(&mut self) {
+        self.apply_replay_events();
+        self.tick_count += 1;
         self.executed_connections = vec![];
         self.changed_variables = vec![];
         self.characters = HashMap::new();
@@ -1304,12 +1867,61 @@ impl GameData<'_> {
         self.prev_area_characters = self.area_characters.clone();
         self.area_characters = HashMap::new();
 
+        self.create_spatial_grids();
+
+        let minutes = self.clock.minutes_of_day(self.tick_count);
+        let day = self.clock.day(self.tick_count);
+
+        // Regions without players which are not simulated this tick, keep their area state for the next simulated tick
+        let skipped_regions = self.get_skipped_regions();
+        for ((region_id, area_index), list) in &self.prev_area_characters {
+            if skipped_regions.contains_key(region_id) {
+                self.area_characters.insert((*region_id, *area_index), list.clone());
+            }
+        }
+
+        // Pending respawns of suspended regions are paused
+        for ((region_id, _, _), spawn) in self.spawns.iter_mut() {
+            if skipped_regions.get(region_id) == Some(&false) && spawn.next_spawn_tick > self.tick_count {
+                spawn.next_spawn_tick += 1;
+            }
+        }
+
+        // The events emitted during the last tick
+        let events = std::mem::take(&mut self.events);
+
         // Execute behaviors
         for inst_index in 0..self.instances.len() {
 
             self.instances[inst_index].messages = vec![];
             self.instances[inst_index].audio = vec![];
 
+            let old_position = self.instances[inst_index].position;
+
+            self.scopes[inst_index].set_value("hour", (minutes / 60) as f64);
+            self.scopes[inst_index].set_value("minute", (minutes % 60) as f64);
+            self.scopes[inst_index].set_value("day", day as f64);
+
+            if events.is_empty() == false {
+                self.deliver_events(inst_index, &events);
+            }
+
+            let mut skipped : Option<bool> = None;
+            if self.instances[inst_index].instance_type == BehaviorInstanceType::NonPlayerCharacter {
+                if let Some(position) = old_position {
+                    skipped = skipped_regions.get(&position.0).copied();
+                }
+            }
+
+            if let Some(run_timers) = skipped {
+                // Reduced or suspended simulation
+                if run_timers == false {
+                    self.instances[inst_index].suspended_ticks += 1;
+                } else
+                if self.instances[inst_index].sleep_cycles > 0 {
+                    self.instances[inst_index].sleep_cycles -= 1;
+                }
+            } else
             // Skip Sleep cycles
             if self.instances[inst_index].sleep_cycles > 0 {
                 self.instances[inst_index].sleep_cycles -= 1;
@@ -1341,6 +1953,21 @@ impl GameData<'_> {
                         }
                     }
                 } else {
+                    // Close the conversation when the partner is gone or the player walked away
+                    if let Some(dialog) = &self.instances[inst_index].dialog {
+                        if self.instances[dialog.npc_index].state != BehaviorInstanceState::Normal || is_adjacent(inst_index, dialog.npc_index, self) == false {
+                            self.instances[inst_index].dialog = None;
+                        }
+                    }
+
+                    // Choices of a conversation are handled directly
+                    if let Some(action) = self.instances[inst_index].action.clone() {
+                        if action.action == "choice" {
+                            self.choose_dialog_option(inst_index, &action.text);
+                            self.instances[inst_index].action = None;
+                        }
+                    }
+
                     // Execute the tree which matches the current action, i.e. "onXXX", like "onMove"
 
                     let mut tree_id: Option<usize> = None;
@@ -1364,9 +1991,13 @@ impl GameData<'_> {
 
                         self.instances[inst_index].action = None;
                     }
+
+                    nodes_quest::update_quests(inst_index, self);
                 }
             }
 
+            self.update_instance_position(inst_index, old_position);
+
             // Add to the characters
 
             if let Some(position) = self.instances[inst_index].position {
@@ -1388,11 +2019,14 @@ impl GameData<'_> {
 
         // Execute region area behaviors
         for i in 0..self.regions_ids.len() {
+            if skipped_regions.contains_key(&self.regions_ids[i]) {
+                continue;
+            }
             let mut to_execute: Vec<(usize, usize)> = vec![];
             if let Some(region) = self.regions.get_mut(&self.regions_ids[i]) {
                 region.displacements = HashMap::new();
                 for area_index in 0..region.data.areas.len() {
-                    for (node_id, node) in &region.behaviors[area_index].data.nodes {
+                    for (node_id, node) in region.behaviors[area_index].data.nodes.iter().sorted_by_key(|(id, _)| **id) {
                         if node.behavior_type == BehaviorNodeType::InsideArea || node.behavior_type == BehaviorNodeType::EnterArea || node.behavior_type == BehaviorNodeType::LeaveArea || node.behavior_type == BehaviorNodeType::Always {
                             to_execute.push((area_index, *node_id));
                         }
@@ -1407,7 +2041,7 @@ impl GameData<'_> {
         // Parse the player characters and generate updates
 
         for inst_index in 0..self.instances.len() {
-            if self.instances[inst_index].instance_type == BehaviorInstanceType::Player {
+            if self.instances[inst_index].instance_type == BehaviorInstanceType::Player && self.instances[inst_index].state != BehaviorInstanceState::Purged {
 
                 // Set the player index
                 self.curr_player_inst_index = inst_index;
@@ -1419,7 +2053,7 @@ impl GameData<'_> {
                 if let Some(game_inst_index) = self.game_instance_index {
                     if self.scopes.is_empty() == false {
                         if let Some(locked_tree) = self.instances[game_inst_index].locked_tree {
-                            self.execute_game_node(game_inst_index, locked_tree);
+                            self.execute_game_tree(game_inst_index, locked_tree);
                         }
                     }
                 }
@@ -1440,6 +2074,7 @@ impl GameData<'_> {
                 let mut characters    : Vec<CharacterData> = vec![];
                 let mut displacements : HashMap<(isize, isize), (usize, usize, usize, TileUsage)> = HashMap::new();
                 let mut lights        : Vec<Light> = vec![];
+                let mut loot          : Vec<((isize, isize), ItemData)> = vec![];
 
                 if let Some(position) = self.instances[inst_index].position {
 
@@ -1461,23 +2096,85 @@ impl GameData<'_> {
                     if self.lights.contains_key(&position.0) {
                         lights = self.lights[&position.0].clone();
                     }
+
+                    // The items lying in the region, sorted to keep the delta updates small
+                    for (pos, items) in self.loot.iter().sorted_by_key(|(pos, _)| **pos) {
+                        if pos.0 == position.0 {
+                            for item in items {
+                                loot.push(((pos.1, pos.2), item.clone()));
+                            }
+                        }
+                    }
+                }
+
+                let mut update = GameUpdate::new();
+                update.position = self.instances[inst_index].position;
+                update.tile = self.instances[inst_index].tile;
+                update.screen = screen;
+                update.region = region;
+                update.lights = lights;
+                update.displacements = displacements;
+                update.characters = characters;
+                update.loot = loot;
+                update.inventory = self.instances[inst_index].inventory.clone();
+                update.messages = self.instances[inst_index].messages.clone();
+                update.audio = self.instances[inst_index].audio.clone();
+                update.time = minutes;
+                update.globals = self.globals.to_vec();
+                update.dialog = self.instances[inst_index].dialog.as_ref().map(|dialog| dialog.data.clone());
+                update.journal = nodes_quest::get_journal(inst_index, self);
+
+                // The other party members, they may be in another region
+                for index in get_party_members(inst_index, self) {
+                    if index != inst_index {
+                        if let Some(position) = self.instances[index].position {
+                            if let Some(tile) = self.instances[index].tile {
+                                update.party.push(CharacterData { position, tile, name: self.instances[index].name.clone(), id: self.instances[index].id, index });
+                            }
+                        }
+                    }
                 }
 
-                let update = GameUpdate{
-                    position                : self.instances[inst_index].position,
-                    tile                    : self.instances[inst_index].tile,
-                    screen                  : screen,
-                    region,
-                    lights,
-                    displacements,
-                    characters,
-                    messages                : self.instances[inst_index].messages.clone(),
-                    audio                   : self.instances[inst_index].audio.clone(),
-                 };
+                if self.delta_updates {
+                    self.instances[inst_index].update = Some(self.create_delta_update(inst_index, update));
+                } else {
+                    self.instances[inst_index].update = Some(update);
+                }
+            }
+        }
+
+        self.report_changed_globals();
+    }
+
+    /// Turns the full update of the given player instance into a delta update if possible and remembers the new state
+    fn create_delta_update(&mut self, inst_index: usize, update: GameUpdate) -> GameUpdate {
 
-                self.instances[inst_index].update = serde_json::to_string(&update).ok();
+        let mut rc : Option<GameUpdate> = None;
+
+        if let Some(last_update) = &self.instances[inst_index].last_update {
+            let region_changed = last_update.position.map(|p| p.0) != update.position.map(|p| p.0);
+            let keyframe_due = self.instances[inst_index].updates_since_keyframe + 1 >= self.keyframe_interval;
+
+            if region_changed == false && keyframe_due == false && update.region.is_none() {
+                rc = Some(update.diff(last_update));
             }
         }
+
+        // The state for the next diff, events are not part of it
+        let mut state = update.clone();
+        state.screen = None;
+        state.region = None;
+        state.messages = vec![];
+        state.audio = vec![];
+        self.instances[inst_index].last_update = Some(state);
+
+        if let Some(delta) = rc {
+            self.instances[inst_index].updates_since_keyframe += 1;
+            delta
+        } else {
+            self.instances[inst_index].updates_since_keyframe = 0;
+            update
+        }
     }
 
     /// Clear the game instances
@@ -1489,10 +2186,16 @@ impl GameData<'_> {
         self.changed_variables = vec![];
         self.player_ids_inst_indices = HashMap::new();
         self.game_player_scopes = HashMap::new();
+        self.loot = HashMap::new();
+        self.spawns = HashMap::new();
+        self.paths = HashMap::new();
+        self.events = vec![];
     }
 
     /// Creates a new player instance and returns the region id the player is located in
     pub fn create_player_instance(&mut self, player_id: usize) -> Option<usize> {
+        self.record_event(ReplayEventType::PlayerJoined(player_id));
+
         let index = self.create_behavior_instance(self.player_behavior_id);
         self.instances[index].instance_type = BehaviorInstanceType::Player;
         self.player_ids_inst_indices.insert(player_id, index);
@@ -1503,16 +2206,76 @@ impl GameData<'_> {
         None
     }
 
+    /// Removes the player instance of the given player id, i.e. when a client disconnects
+    pub fn remove_player_instance(&mut self, player_id: usize) {
+        if let Some(index) = self.player_ids_inst_indices.remove(&player_id) {
+            self.record_event(ReplayEventType::PlayerLeft(player_id));
+
+            // Instance indices are referenced everywhere, so we purge the instance instead of removing it
+            self.instances[index].state = BehaviorInstanceState::Purged;
+            self.instances[index].position = None;
+            self.instances[index].action = None;
+            self.instances[index].update = None;
+            self.instances[index].sleep_cycles = 0;
+        }
+    }
+
     /// Assign an action to an instance
     pub fn execute_packed_instance_action(&mut self, action: String) {
         if let Some(action) = serde_json::from_str::<PlayerAction>(&action).ok() {
-            if let Some(index) = self.player_ids_inst_indices.get(&action.player_id) {
-                self.instances[*index].action = Some(action);
+            self.execute_instance_action(action);
+        }
+    }
+
+    /// Assign an action to the instance of the player id of the action
+    pub fn execute_instance_action(&mut self, action: PlayerAction) {
+
+        // While replaying only the recorded actions are executed
+        if self.replay.is_some() {
+            return;
+        }
+        self.record_event(ReplayEventType::Action(action.clone()));
+        self.apply_instance_action(action);
+    }
+
+    /// Executes the action without recording it
+    fn apply_instance_action(&mut self, action: PlayerAction) {
+
+        // Save slots are handled directly
+        if action.action == "save" {
+            self.save_to_slot(&action.text);
+            return;
+        } else
+        if action.action == "load" {
+            self.load_from_slot(&action.text);
+            return;
+        } else
+        if action.action == "global" {
+            // Global variable changed by a screen script, "name=value". Only variables marked as writable
+            // by screen scripts in the game behavior can be changed by the clients.
+            if let Some((name, value)) = action.text.split_once('=') {
+                if let Some(value) = value.trim().parse::<f64>().ok() {
+                    if self.player_ids_inst_indices.contains_key(&action.player_id) && self.globals.is_screen_writable(name.trim()) {
+                        self.globals.set(name.trim(), value);
+                    }
+                }
             }
+            return;
+        }
+
+        if let Some(index) = self.player_ids_inst_indices.get(&action.player_id) {
+            self.instances[*index].action = Some(action);
         }
     }
 
+    /// Starts the game with a local player
     pub fn startup(&mut self) {
+        self.startup_server();
+        _ = self.create_player_instance(131313)
+    }
+
+    /// Starts the game without any players, they are created when clients connect
+    pub fn startup_server(&mut self) {
 
         self.asset = Some(Asset::new());
         #[cfg(not(feature = "embed_binaries"))]
@@ -1520,12 +2283,345 @@ impl GameData<'_> {
         #[cfg(feature = "embed_binaries")]
         self.asset.as_mut().unwrap().load_from_embedded();
 
+        self.create_region_graph();
+        self.read_clock_settings();
+        self.create_globals();
+        self.create_factions();
+        self.create_quests();
         self.create_behavior_instances();
+        self.create_item_instances();
         self.game_instance_index = Some(self.create_game_instance());
-        _ = self.create_player_instance(131313)
+    }
+
+    /// Returns the regions which are not simulated in this tick based on their "simulation" setting. Regions with
+    /// players are always simulated. The value is true if the timers keep running, for false (suspended) the sleep
+    /// cycles, Cooldown nodes and respawn delays pause. The clock, and with it the Time and Schedule nodes, never pauses.
+    pub fn get_skipped_regions(&self) -> HashMap<usize, bool> {
+        let mut skipped = HashMap::new();
+
+        let mut player_regions : Vec<usize> = vec![];
+        for instance in &self.instances {
+            if instance.instance_type == BehaviorInstanceType::Player && instance.state != BehaviorInstanceState::Purged {
+                if let Some(position) = instance.position {
+                    player_regions.push(position.0);
+                }
+            }
+        }
+
+        for (id, region) in &self.regions {
+            if player_regions.contains(id) {
+                continue;
+            }
+
+            let mut mode = "full".to_string();
+            if let Some(property) = region.data.settings.get("simulation") {
+                if let Some(value) = property.as_string() {
+                    mode = value.to_lowercase();
+                }
+            }
+
+            if mode == "reduced" {
+                let mut rate = 4;
+                if let Some(property) = region.data.settings.get("simulation_rate") {
+                    if let Some(value) = property.as_int() {
+                        rate = value.max(1) as usize;
+                    }
+                }
+                if self.tick_count % rate != 0 {
+                    skipped.insert(*id, true);
+                }
+            } else
+            if mode == "suspended" {
+                skipped.insert(*id, false);
+            }
+        }
+        skipped
+    }
+
+    /// Rebuilds the spatial grids of all regions from the instance positions
+    pub fn create_spatial_grids(&mut self) {
+        for region in self.regions.values_mut() {
+            region.grid.clear();
+        }
+        for (index, instance) in self.instances.iter().enumerate() {
+            if let Some(position) = instance.position {
+                if let Some(region) = self.regions.get_mut(&position.0) {
+                    region.grid.add(index, (position.1, position.2));
+                }
+            }
+        }
+    }
+
+    /// Moves the instance inside the spatial grids after its position changed from the given old position
+    pub fn update_instance_position(&mut self, index: usize, old_position: Option<(usize, isize, isize)>) {
+        let position = self.instances[index].position;
+        if position == old_position {
+            return;
+        }
+        if let Some(old) = old_position {
+            if let Some(region) = self.regions.get_mut(&old.0) {
+                region.grid.remove(index, (old.1, old.2));
+            }
+        }
+        if let Some(new) = position {
+            if let Some(region) = self.regions.get_mut(&new.0) {
+                region.grid.add(index, (new.1, new.2));
+            }
+        }
+    }
+
+    /// Returns the active characters inside the given area of a region, sorted by index
+    pub fn get_instances_in_area(&self, region_id: usize, area_index: usize) -> Vec<usize> {
+        let mut rc = vec![];
+        if let Some(region) = self.regions.get(&region_id) {
+            let area = &region.data.areas[area_index].area;
+
+            // Large areas are cheaper to check against the instances of the region
+            let candidates = if area.len() > region.grid.len() {
+                region.grid.all().into_iter().filter(|index| {
+                    if let Some(p) = self.instances[*index].position { area.contains(&(p.1, p.2)) } else { false }
+                }).collect()
+            } else {
+                let mut list = vec![];
+                for pos in area {
+                    list.append(&mut region.grid.at(*pos));
+                }
+                list.sort();
+                list.dedup();
+                list
+            };
+
+            for index in candidates {
+                let instance = &self.instances[index];
+                if instance.tile.is_some() && instance.state != BehaviorInstanceState::Killed && instance.state != BehaviorInstanceState::Purged {
+                    rc.push(index);
+                }
+            }
+        }
+        rc
+    }
+
+    /// Reads the ticks per minute and the start time of the clock from the game settings
+    pub fn read_clock_settings(&mut self) {
+        self.clock = GameClock::new();
+        for (_id, node) in &self.game.behavior.data.nodes {
+            if node.behavior_type == BehaviorNodeType::BehaviorType {
+                if let Some(value) = node.values.get("ticks_per_minute") {
+                    if value.0 >= 1.0 {
+                        self.clock.ticks_per_minute = value.0 as usize;
+                    }
+                }
+                if let Some(value) = node.values.get("start_time") {
+                    if let Some(minutes) = parse_time_of_day(&value.4) {
+                        self.clock.start_minutes = minutes;
+                    } else {
+                        println!("Invalid start time \"{}\", use the HH:MM format", value.4);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Creates the reputations between the factions from the game settings
+    pub fn create_factions(&mut self) {
+        self.factions.clear();
+        for (_id, node) in &self.game.behavior.data.nodes {
+            if node.behavior_type == BehaviorNodeType::BehaviorType {
+                if let Some(value) = node.values.get("factions") {
+                    for (a, b, reputation) in parse_reputations(&value.4) {
+                        self.factions.set(&a, &b, reputation);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses the Quest nodes of the game behavior
+    pub fn create_quests(&mut self) {
+        self.quests = vec![];
+        for (_id, node) in self.game.behavior.data.nodes.iter().sorted_by_key(|(id, _)| **id) {
+            if node.behavior_type == BehaviorNodeType::Quest {
+                self.quests.push(parse_quest(node));
+            }
+        }
+    }
+
+    /// Creates the global variables from the number variables of the game behavior
+    pub fn create_globals(&mut self) {
+        self.globals.clear();
+        for (_id, node) in &self.game.behavior.data.nodes {
+            if node.behavior_type == BehaviorNodeType::VariableNumber {
+                if let Some(value) = node.values.get("value") {
+                    self.globals.define(&node.name, value.0);
+                } else {
+                    self.globals.define(&node.name, 0.0);
+                }
+            } else
+            if node.behavior_type == BehaviorNodeType::BehaviorType {
+                // The globals screen scripts may write, separated by commas
+                if let Some(value) = node.values.get("screen_globals") {
+                    for name in value.4.split(',') {
+                        if name.trim().is_empty() == false {
+                            self.globals.set_screen_writable(name.trim());
+                        }
+                    }
+                }
+            }
+        }
+        self.area_scope = Scope::new();
+        self.area_scope.push("globals", self.globals.clone());
+        self.area_scope.push("factions", self.factions.clone());
+    }
+
+    /// Executes the "On Event" trees of the instance for the given events, the trees are identified by the name of the event
+    fn deliver_events(&mut self, inst_index: usize, events: &Vec<GameEvent>) {
+        if self.instances[inst_index].state == BehaviorInstanceState::Killed || self.instances[inst_index].state == BehaviorInstanceState::Purged {
+            return;
+        }
+
+        for event in events {
+            if event.receivers.contains(&inst_index) == false {
+                continue;
+            }
+
+            let mut trees = vec![];
+            if let Some(behavior) = self.behaviors.get(&self.instances[inst_index].behavior_id) {
+                for tree_id in &self.instances[inst_index].tree_ids {
+                    if let Some(node) = behavior.data.nodes.get(tree_id) {
+                        if node.name == event.name {
+                            if let Some(value) = node.values.get("execute") {
+                                if value.0 == 3.0 {
+                                    trees.push(*tree_id);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if trees.is_empty() == false {
+                self.scopes[inst_index].set_value("event_payload", event.payload);
+                self.scopes[inst_index].set_value("event_sender", event.sender.clone());
+                for tree_id in trees {
+                    self.execute_node(inst_index, tree_id);
+                }
+            }
+        }
+    }
+
+    /// Executes the nodes connected to the picked choice (the index in the text of the action) of the open conversation of the player
+    fn choose_dialog_option(&mut self, inst_index: usize, text: &str) {
+        if let Some(dialog) = self.instances[inst_index].dialog.take() {
+            if let Some(choice) = text.trim().parse::<usize>().ok() {
+                if let Some(connector) = dialog.connectors.get(choice) {
+                    let npc_index = dialog.npc_index;
+                    self.instances[npc_index].target_instance_index = Some(inst_index);
+                    let behavior_id = self.instances[npc_index].behavior_id;
+                    for node_id in self.get_connected_nodes(BehaviorType::Behaviors, behavior_id, dialog.node_id, *connector) {
+                        self.executed_connections.push((BehaviorType::Behaviors, dialog.node_id, *connector));
+                        self.execute_node(npc_index, node_id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the global variables changed during the tick to changed_variables for the editor
+    fn report_changed_globals(&mut self) {
+        for name in self.globals.take_changed() {
+            if let Some(value) = self.globals.get(&name) {
+                for (_id, node) in &self.game.behavior.data.nodes {
+                    if node.behavior_type == BehaviorNodeType::VariableNumber && node.name == name {
+                        self.changed_variables.push((self.game_instance_index.unwrap_or(0), self.game.behavior.data.id, node.id, value));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Collects the teleport areas of all regions, used to route characters between regions
+    pub fn create_region_graph(&mut self) {
+        self.portals = HashMap::new();
+        for (id, region) in &self.regions {
+            self.portals.insert(*id, region.get_portals());
+        }
+    }
+
+    /// Switches to deterministic mode, all random numbers of the simulation are derived from the given seed.
+    /// Call before startup() to get reproducible runs.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+        *self.rng.borrow_mut() = ChaCha8Rng::seed_from_u64(seed);
+    }
+
+    /// Starts recording all player input, call before startup(). Switches to deterministic mode if no seed is set.
+    /// The player id is the player of the local client, if any.
+    pub fn start_recording(&mut self, player_id: Option<usize>) {
+        let seed = match self.seed {
+            Some(seed) => seed,
+            None => {
+                let seed = thread_rng().gen();
+                self.set_seed(seed);
+                seed
+            }
+        };
+        self.recording = Some(Replay::new(seed, player_id));
+    }
+
+    /// Stops the recording and returns the recorded session
+    pub fn stop_recording(&mut self) -> Option<Replay> {
+        if let Some(mut replay) = self.recording.take() {
+            replay.ticks = self.tick_count;
+            return Some(replay);
+        }
+        None
+    }
+
+    /// Starts the game without players and replays the given session, the players join as recorded.
+    /// Actions passed to execute_instance_action() are ignored while replaying.
+    pub fn start_replay(&mut self, replay: Replay) {
+        self.set_seed(replay.seed);
+        self.startup_server();
+        self.replay = Some((replay, 0));
+    }
+
+    /// True if all ticks of the replayed session have been executed
+    pub fn replay_finished(&self) -> bool {
+        if let Some((replay, _)) = &self.replay {
+            return self.tick_count >= replay.ticks;
+        }
+        false
+    }
+
+    /// Adds the event to the recording, if any
+    fn record_event(&mut self, event: ReplayEventType) {
+        if let Some(recording) = &mut self.recording {
+            recording.events.push(ReplayEvent { tick: self.tick_count, event });
+        }
+    }
+
+    /// Applies the replay events recorded before the upcoming tick
+    fn apply_replay_events(&mut self) {
+        loop {
+            let mut event = None;
+            if let Some((replay, index)) = &mut self.replay {
+                if *index < replay.events.len() && replay.events[*index].tick <= self.tick_count {
+                    event = Some(replay.events[*index].event.clone());
+                    *index += 1;
+                }
+            }
+
+            match event {
+                Some(ReplayEventType::PlayerJoined(player_id)) => _ = self.create_player_instance(player_id),
+                Some(ReplayEventType::PlayerLeft(player_id)) => self.remove_player_instance(player_id),
+                Some(ReplayEventType::Action(action)) => self.apply_instance_action(action),
+                None => break,
+            }
+        }
     }
 
     pub fn shutdown(&mut self) {
+        self.replay = None;
         self.clear_instances();
         self.game_instance_index = None;
         self.asset = None;
@@ -1534,10 +2630,109 @@ impl GameData<'_> {
     /// Locally poll a player update, this is used for local single player games
     pub fn poll_update(&mut self, player_id: usize) -> Option<String> {
         if let Some(index) = self.player_ids_inst_indices.get(&player_id) {
-            let upd = self.instances[*index].update.clone();
-            //self.instances[*index].update = None;
-            return upd;
+            if let Some(update) = &self.instances[*index].update {
+                return serde_json::to_string(update).ok();
+            }
+        }
+        None
+    }
+
+    /// Poll a player update encoded with the given codec, used by network servers
+    pub fn poll_update_encoded(&mut self, player_id: usize, codec: Codec) -> Option<Vec<u8>> {
+        if let Some(index) = self.player_ids_inst_indices.get(&player_id) {
+            if let Some(update) = &self.instances[*index].update {
+                return codec.encode(update);
+            }
         }
         None
     }
-}
\ No newline at end of file
+}
+
+/// The tests use the game of the repository, with embedded binaries it would be loaded from the embedded files
+#[cfg(all(test, not(feature = "embed_binaries")))]
+mod tests {
+    use super::*;
+    use crate::gamedata::nodes_utility::get_faction;
+
+    fn load_game() -> GameData<'static> {
+        GameData::load_from_path(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."))
+    }
+
+    fn parse(json: &str) -> serde_json::Value {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn save_and_load_into_a_new_game() {
+        let mut game = load_game();
+        game.set_seed(7);
+        game.startup();
+        for _ in 0..20 {
+            game.tick();
+        }
+
+        game.regions.get_mut(&542323).unwrap().displacements.insert((21, 3), (0, 1, 2, TileUsage::EnvBlocking));
+        let player = game.player_ids_inst_indices[&131313];
+        game.scopes[player].set_value("Value2", 4.5_f64);
+
+        let saved = game.save();
+        let snapshot = parse(&saved);
+        assert!(snapshot["displacements"].as_array().unwrap().iter().any(|region| region[1].as_array().unwrap().is_empty() == false));
+        assert!(snapshot["rng_word_pos"].as_u64().unwrap() > 0);
+        assert_eq!(snapshot["tick_count"].as_u64(), Some(20));
+
+        let mut restored = load_game();
+        assert!(restored.load(&saved));
+        assert_eq!(parse(&restored.save()), snapshot);
+
+        assert_eq!(restored.instances.len(), game.instances.len());
+        assert_eq!(restored.scopes.len(), game.scopes.len());
+        assert_eq!(restored.scopes[player].get_value::<f64>("Value2"), Some(4.5));
+        assert_eq!(restored.tick_count, 20);
+        assert_eq!(restored.regions[&542323].displacements.get(&(21, 3)), Some(&(0, 1, 2, TileUsage::EnvBlocking)));
+
+        // Both games continue identically
+        assert_eq!(restored.rng.borrow().get_word_pos(), game.rng.borrow().get_word_pos());
+        for _ in 0..20 {
+            game.tick();
+            restored.tick();
+        }
+        assert_eq!(parse(&restored.save()), parse(&game.save()));
+    }
+
+    #[test]
+    fn faction_is_read_from_the_scope() {
+        let mut game = load_game();
+        game.startup();
+
+        let orc = game.instances.iter().position(|instance| instance.name == "Orc").unwrap();
+        let moody = game.instances.iter().position(|instance| instance.name == "Moody").unwrap();
+        game.scopes[orc].set_value("faction", "Orcs".to_string());
+        game.scopes[moody].set_value("faction", "Town".to_string());
+        game.factions.set("Orcs", "Town", -100.0);
+        assert!(nodes_utility::matches_faction_filter(orc, moody, 1, &game));
+
+        // A script changing the faction changes the hostility checks
+        game.engine.eval_with_scope::<rhai::Dynamic>(&mut game.scopes[orc], "faction = \"Town\";").unwrap();
+        assert_eq!(get_faction(orc, &game), "Town");
+        assert!(nodes_utility::matches_faction_filter(orc, moody, 2, &game));
+
+        // The faction survives a save
+        let saved = game.save();
+        let mut restored = load_game();
+        restored.load(&saved);
+        assert_eq!(get_faction(orc, &restored), "Town");
+        assert_eq!(get_faction(moody, &restored), "Town");
+    }
+
+    #[test]
+    fn data_without_id_gets_the_same_id_on_every_run() {
+        let path = PathBuf::from("missing").join("area_1.json");
+        let behavior = GameBehavior::load_from_path(&path, &path);
+        assert_eq!(behavior.data.id, GameBehavior::load_from_path(&path, &path).data.id);
+        assert!(behavior.data.id != GameBehavior::load_from_path(&path.with_file_name("area_2.json"), &path).data.id);
+
+        let path = PathBuf::from("missing");
+        assert_eq!(GameRegion::new(&path, &path).data.id, GameRegion::new(&path, &path).data.id);
+    }
+}
@@ -1,7 +1,8 @@
-use core_shared::message::MessageData;
+use core_shared::message::{MessageData, DialogData};
 use serde::{Deserialize, Serialize};
-use rand::prelude::*;
-use core_shared::actions::PlayerAction;
+use core_shared::actions::{PlayerAction, PlayerDirection};
+use core_shared::update::GameUpdate;
+use core_shared::item::ItemData;
 
 use std::collections::HashSet;
 use std::fs;
@@ -9,6 +10,8 @@ use std::path;
 use std::path::PathBuf;
 
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use itertools::Itertools;
 
 #[cfg(feature = "embed_binaries")]
@@ -56,7 +59,32 @@ pub enum BehaviorNodeType {
     MessageArea,
     AudioArea,
     LightArea,
-    Always
+    Always,
+    Take,
+    Drop,
+    UseItem,
+    TimeOfDay,
+    Schedule,
+    Selector,
+    Inverter,
+    Repeat,
+    Parallel,
+    Cooldown,
+    Random,
+    EmitEvent,
+    Talk,
+    Dialog,
+    Quest,
+    StartQuest,
+    AdvanceQuest,
+    CompleteQuest,
+    AdjustReputation,
+    Recruit,
+    Dismiss,
+    Follow,
+    Wander,
+    Flee,
+    Patrol,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Copy, Clone)]
@@ -127,20 +155,29 @@ pub struct BehaviorInstance {
     // This is for serialization only / deserialization only, not used at runtime
     pub number_values           : HashMap<String, f64>,
 
+    // Store string variables like the faction, serialization only as well
+    #[serde(default)]
+    pub string_values           : HashMap<String, String>,
+
     // An instance id of the entity we are currently interacting with
     pub target_instance_index   : Option<usize>,
 
     // The number of ticks this instance is skipping
     pub sleep_cycles            : usize,
 
+    // The number of ticks this instance spent in a suspended region, Cooldown nodes measure time without them
+    #[serde(skip)]
+    pub suspended_ticks         : usize,
+
     // The locked tree, only this tree will be executed.
     pub locked_tree             : Option<usize>,
 
-    // Instance ids of the entities in our party (including self)
+    // Instance ids of the entities in our party (including self), only the party leader holds the ids
     pub party                   : Vec<usize>,
 
     // Temporary values nodes can use to store instance data, these are NOT saved, i.e. emptied before saving.
     // The key is the behavior type and node id.
+    #[serde(skip)]
     pub node_values             : HashMap<(BehaviorType, usize), (f64, f64, f64, f64, String)>,
 
     // State values to optionally store game state related to this instance. This data is saved.
@@ -150,6 +187,14 @@ pub struct BehaviorInstance {
     pub position                : Option<(usize, isize, isize)>,
     pub tile                    : Option<(usize, usize, usize)>,
 
+    // The items carried by this instance
+    #[serde(default)]
+    pub inventory               : Vec<ItemData>,
+
+    /// The direction of the last step, used for the view cone
+    #[serde(default = "default_facing")]
+    pub facing                  : PlayerDirection,
+
     // Messages for this player in the current tick
     pub messages                : Vec<MessageData>,
 
@@ -159,15 +204,22 @@ pub struct BehaviorInstance {
     /// The current player action
     pub action                  : Option<PlayerAction>,
 
+    /// The open conversation of the player
+    #[serde(default)]
+    pub dialog                  : Option<Dialog>,
+
     // Server side handling of the "Player" character
 
     /// The current player update
-    pub update                  : Option<String>,
+    #[serde(skip)]
+    pub update                  : Option<GameUpdate>,
 
     /// The regions we send to the player client already
+    #[serde(skip)]
     pub regions_send            : HashSet<usize>,
 
-    /// Current screen id
+    /// Current screen id, not saved so that the clients get their screen again after a load
+    #[serde(skip)]
     pub curr_player_screen_id   : Option<usize>,
 
     /// Current screen content
@@ -175,6 +227,73 @@ pub struct BehaviorInstance {
 
     /// The locked tree for the game behavior for this player
     pub game_locked_tree        : Option<usize>,
+
+    /// The state of the last update we send to the player, used to compute delta updates
+    #[serde(skip)]
+    pub last_update             : Option<GameUpdate>,
+
+    /// The amount of delta updates send since the last keyframe
+    #[serde(skip)]
+    pub updates_since_keyframe  : usize,
+}
+
+fn default_facing() -> PlayerDirection {
+    PlayerDirection::None
+}
+
+/// The runtime state of a Spawn area node
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SpawnData {
+    /// The instance indices of the living spawns
+    pub instances               : Vec<usize>,
+
+    /// The tick count at which the next spawn is allowed
+    pub next_spawn_tick         : usize,
+
+    /// The instance indices of dead spawns, respawns reuse them
+    #[serde(default)]
+    pub dead                    : Vec<usize>,
+}
+
+/// An event sent by an Emit Event node, delivered to the "On Event" trees of the receivers on the next tick
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GameEvent {
+    pub name                    : String,
+    pub payload                 : f64,
+
+    /// The name of the sending character or area
+    pub sender                  : String,
+
+    /// The instance indices of the receivers
+    pub receivers               : Vec<usize>,
+}
+
+/// A conversation of a player with a character, waiting for the player to pick one of the choices of the Dialog node
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Dialog {
+    /// The instance index of the character the player talks to
+    pub npc_index               : usize,
+
+    /// The id of the Dialog node in the behavior of the character
+    pub node_id                 : usize,
+
+    /// The connectors of the choices
+    pub connectors              : Vec<BehaviorNodeConnector>,
+
+    pub data                    : DialogData,
+}
+
+/// A path calculated by walk_towards, reused until the destination, the map or the blockers change
+#[derive(Clone, Debug, Default)]
+pub struct CachedPath {
+    pub region                  : usize,
+    pub destination             : (isize, isize),
+
+    /// Hash of the tile displacements and the blocked tiles on the remaining steps the path was calculated with
+    pub signature               : u64,
+
+    /// The remaining steps, starting with the current position
+    pub steps                   : Vec<(isize, isize)>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -195,6 +314,13 @@ pub struct GameBehavior {
     pub data                    : GameBehaviorData,
 }
 
+/// The id of data which has no id stored yet, derived from its name or path so that it is the same on every run
+pub fn default_data_id(name: &str) -> usize {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    (hasher.finish() % u32::MAX as u64) as usize + 1
+}
+
 impl GameBehavior {
     pub fn load_from_path(path: &PathBuf, behavior_path: &PathBuf) -> Self {
 
@@ -206,7 +332,7 @@ impl GameBehavior {
 
         // Construct the json settings
         let data = serde_json::from_str(&contents)
-            .unwrap_or(GameBehaviorData { nodes: HashMap::new(), connections: vec![], id: thread_rng().gen_range(1..=u32::MAX) as usize, name: "New Behavior".to_string(), curr_node_id: None });
+            .unwrap_or(GameBehaviorData { nodes: HashMap::new(), connections: vec![], id: default_data_id(&path.to_string_lossy()), name: "New Behavior".to_string(), curr_node_id: None });
 
         Self {
             name            : name.to_string(),
@@ -222,7 +348,7 @@ impl GameBehavior {
         let name = path::Path::new(&file_name).file_stem().unwrap().to_str().unwrap();
 
         // Construct the json settings
-        let mut data = GameBehaviorData { nodes: HashMap::new(), connections: vec![], id: thread_rng().gen_range(1..=u32::MAX) as usize, name: "New Behavior".to_string(), curr_node_id: None };
+        let mut data = GameBehaviorData { nodes: HashMap::new(), connections: vec![], id: default_data_id(file_name), name: "New Behavior".to_string(), curr_node_id: None };
 
         if let Some(bytes) = Embedded::get(file_name) {
             if let Some(string) = std::str::from_utf8(bytes.data.as_ref()).ok() {
@@ -244,7 +370,7 @@ impl GameBehavior {
             name            : "name".to_string(),
             path            : std::path::Path::new("").to_path_buf(),
             behavior_path   : std::path::Path::new("").to_path_buf(),
-            data            : GameBehaviorData { nodes: HashMap::new(), connections: vec![], id: thread_rng().gen_range(1..=u32::MAX) as usize, name: "New Behavior".to_string(), curr_node_id: None }
+            data            : GameBehaviorData { nodes: HashMap::new(), connections: vec![], id: default_data_id("name"), name: "New Behavior".to_string(), curr_node_id: None }
         }
     }
 
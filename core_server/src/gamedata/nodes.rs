@@ -1,18 +1,30 @@
 use core_shared::actions::PlayerDirection;
-use core_shared::message::MessageData;
+use core_shared::message::{MessageData, DialogData};
+use core_shared::clock::{ parse_time_of_day, is_time_in_range };
 
 use crate::gamedata::behavior:: { BehaviorNodeConnector, BehaviorNodeType };
 use crate::gamedata::GameData;
 
+use rand::Rng;
+use itertools::Itertools;
+
 use crate::gamedata::nodes_utility::*;
+use crate::gamedata::nodes_quest::{ get_quest_owner, quest_killed, quest_talked_to };
 use crate::gamedata::script::*;
 
 use super::MessageType;
-use super::behavior::{BehaviorType, BehaviorInstanceState};
+use super::behavior::{BehaviorType, BehaviorInstanceState, BehaviorInstanceType, GameEvent, Dialog};
 
-/// expression
+/// expression, area behaviors pass the region id as instance_index and evaluate in the area scope
 pub fn expression(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
 
+    if behavior_type == BehaviorType::Regions {
+        if eval_bool_expression_area(instance_index, (id.0, id.1, "expression".to_string()), data) == Some(true) {
+            return BehaviorNodeConnector::Right;
+        }
+        return BehaviorNodeConnector::Fail;
+    }
+
     let rc = eval_bool_expression_instance(instance_index, (behavior_type, id.0, id.1, "expression".to_string()), data);
     if let Some(rc) = rc {
         if rc == true {
@@ -22,8 +34,41 @@ pub fn expression(instance_index: usize, id: (usize, usize), data: &mut GameData
     BehaviorNodeConnector::Fail
 }
 
+/// Time of Day, succeeds if the time of the game clock lies inside the from - to range.
+/// Also used by area behaviors where the id of the region is passed as instance_index.
+pub fn time_of_day(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let region_id = if behavior_type == BehaviorType::Regions { instance_index } else { 0 };
+
+    let mut from = 6 * 60;
+    if let Some(value) = get_node_value((id.0, id.1, "from"), data, behavior_type, region_id) {
+        if let Some(minutes) = parse_time_of_day(&value.4) {
+            from = minutes;
+        }
+    }
+
+    let mut to = 18 * 60;
+    if let Some(value) = get_node_value((id.0, id.1, "to"), data, behavior_type, region_id) {
+        if let Some(minutes) = parse_time_of_day(&value.4) {
+            to = minutes;
+        }
+    }
+
+    if is_time_in_range(data.clock.minutes_of_day(data.tick_count), from, to) {
+        if behavior_type == BehaviorType::Regions {
+            return BehaviorNodeConnector::Right;
+        }
+        return BehaviorNodeConnector::Success;
+    }
+    BehaviorNodeConnector::Fail
+}
+
 /// script
 pub fn script(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+    if behavior_type == BehaviorType::Regions {
+        _ = eval_dynamic_script_area(instance_index, (id.0, id.1, "script".to_string()), data);
+        return BehaviorNodeConnector::Right;
+    }
     _ = eval_dynamic_script_instance(instance_index, (behavior_type, id.0, id.1, "script".to_string()), data);
     BehaviorNodeConnector::Bottom
 }
@@ -41,6 +86,7 @@ pub fn message(instance_index: usize, id: (usize, usize), data: &mut GameData, b
             2 => MessageType::Yell,
             3 => MessageType::Private,
             4 => MessageType::Debug,
+            5 => MessageType::Party,
             _ => MessageType::Status
         }
     }
@@ -52,16 +98,7 @@ pub fn message(instance_index: usize, id: (usize, usize), data: &mut GameData, b
     }
 
     // Do I need to evaluate the script for variables ?
-    if text.contains("${") {
-        data.scopes[instance_index].push("Self", data.instances[instance_index].name.clone());
-        if let Some(target_index) = data.instances[instance_index].target_instance_index {
-            data.scopes[instance_index].push("Target", data.instances[target_index].name.clone());
-        }
-        let r = data.engine.eval_with_scope::<String>(&mut data.scopes[instance_index], format!("`{}`", text).as_str());
-        if let Some(rc) = r.ok() {
-            text = rc;
-        }
-    }
+    text = interpolate_text(instance_index, text, data);
 
     // Formating if needed
     text = match message_type {
@@ -71,9 +108,20 @@ pub fn message(instance_index: usize, id: (usize, usize), data: &mut GameData, b
 
     let message_data = MessageData { message_type, message: text.clone(), from: data.instances[instance_index].name.clone() };
 
-     data.instances[instance_index].messages.push(message_data.clone());
-    if let Some(target_index) = data.instances[instance_index].target_instance_index {
-        data.instances[target_index].messages.push(message_data);
+    if message_type == MessageType::Party {
+        // Party messages go to every member of the party of the instance
+        let mut members = get_party_members(instance_index, data);
+        if members.is_empty() {
+            members.push(instance_index);
+        }
+        for index in members {
+            data.instances[index].messages.push(message_data.clone());
+        }
+    } else {
+        data.instances[instance_index].messages.push(message_data.clone());
+        if let Some(target_index) = data.instances[instance_index].target_instance_index {
+            data.instances[target_index].messages.push(message_data);
+        }
     }
 
     // Output it
@@ -81,6 +129,83 @@ pub fn message(instance_index: usize, id: (usize, usize), data: &mut GameData, b
     BehaviorNodeConnector::Bottom
 }
 
+/// Emit Event, sends the event to the target, to everyone in the area of the sender or to everyone in the region.
+/// The event is delivered to the "On Event" trees of the receivers on the next tick. Area behaviors pass the
+/// region id as instance_index, for them the target are the characters inside the area.
+pub fn emit_event(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let region_id = if behavior_type == BehaviorType::Regions { instance_index } else { 0 };
+    let connector = if behavior_type == BehaviorType::Regions { BehaviorNodeConnector::Right } else { BehaviorNodeConnector::Bottom };
+
+    let mut name = "".to_string();
+    if let Some(value) = get_node_value((id.0, id.1, "event"), data, behavior_type, region_id) {
+        name = value.4.trim().to_string();
+    }
+    if name.is_empty() {
+        return connector;
+    }
+
+    let mut to = 0;
+    if let Some(value) = get_node_value((id.0, id.1, "to"), data, behavior_type, region_id) {
+        to = value.0 as usize;
+    }
+
+    let payload;
+    let sender;
+    let mut receivers : Vec<usize> = vec![];
+
+    if behavior_type == BehaviorType::Regions {
+        payload = eval_number_expression_area(region_id, (id.0, id.1, "payload".to_string()), data).unwrap_or(0.0);
+        sender = if let Some(region) = data.regions.get(&region_id) { region.data.areas[id.0].name.clone() } else { "".to_string() };
+
+        if to == 2 {
+            if let Some(region) = data.regions.get(&region_id) {
+                receivers = region.grid.all();
+            }
+        } else {
+            receivers = data.get_instances_in_area(region_id, id.0);
+        }
+    } else {
+        payload = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "payload".to_string()), data).unwrap_or(0.0);
+        sender = data.instances[instance_index].name.clone();
+
+        if to == 0 {
+            if let Some(target_index) = data.instances[instance_index].target_instance_index {
+                receivers.push(target_index);
+            }
+        } else
+        if let Some(p) = data.instances[instance_index].position {
+            if to == 1 {
+                let mut areas = vec![];
+                if let Some(region) = data.regions.get(&p.0) {
+                    for (area_index, area) in region.data.areas.iter().enumerate() {
+                        if area.area.contains(&(p.1, p.2)) {
+                            areas.push(area_index);
+                        }
+                    }
+                }
+                for area_index in areas {
+                    receivers.append(&mut data.get_instances_in_area(p.0, area_index));
+                }
+                receivers.sort();
+                receivers.dedup();
+            } else
+            if let Some(region) = data.regions.get(&p.0) {
+                receivers = region.grid.all();
+            }
+        }
+    }
+
+    if behavior_type != BehaviorType::Regions {
+        receivers.retain(|index| *index != instance_index);
+    }
+
+    if receivers.is_empty() == false {
+        data.events.push(GameEvent { name, payload, sender, receivers });
+    }
+    connector
+}
+
 /// Pathfinder
 pub fn pathfinder(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
 
@@ -98,20 +223,15 @@ pub fn pathfinder(instance_index: usize, id: (usize, usize), data: &mut GameData
             if let Some(value) = node.values.get("destination") {
                 dp = Some((value.0 as usize, value.1 as isize, value.2 as isize));
                 if let Some(p) = p {
-                    distance = compute_distance(&p, &dp.unwrap()).round();
+                    if p.0 == value.0 as usize {
+                        distance = compute_distance(&p, &dp.unwrap()).round();
+                    }
                 }
             }
         }
     }
 
-    let mut speed : f64 = 8.0;
-    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "speed".to_string()), data) {
-        speed = rc;
-    }
-
-    // Apply the speed delay
-    let delay = 10.0 - speed.clamp(0.0, 10.0);
-    data.instances[instance_index].sleep_cycles = delay as usize;
+    apply_speed_delay(instance_index, id, data, behavior_type);
 
     // Success if we reached the to_distance already
     if distance == 0.0 {
@@ -121,6 +241,247 @@ pub fn pathfinder(instance_index: usize, id: (usize, usize), data: &mut GameData
     walk_towards(instance_index, p, dp,false, data)
 }
 
+/// Wander, walks to random tiles of the given area or, without an area, within the radius around the position
+/// where the character started wandering. The destination and the home position are kept in the state values.
+pub fn wander(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let p = match data.instances[instance_index].position {
+        Some(p) => p,
+        None => return BehaviorNodeConnector::Fail,
+    };
+
+    let mut area = "".to_string();
+    if let Some(value) = get_node_value((id.0, id.1, "area"), data, behavior_type, 0) {
+        area = value.4.trim().to_string();
+    }
+
+    let mut radius = 5.0;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "radius".to_string()), data) {
+        radius = rc;
+    }
+
+    apply_speed_delay(instance_index, id, data, behavior_type);
+
+    // Destination (0, 1), -1 if there is none, home (2, 3) and the region of home
+    let key = format!("wander:{}:{}", id.0, id.1);
+    let mut values = (-1.0, -1.0, p.1 as f64, p.2 as f64, p.0.to_string());
+    if let Some(v) = data.instances[instance_index].state_values.get(&key) {
+        if v.4 == p.0.to_string() {
+            values = v.clone();
+        }
+    }
+
+    if values.0 < 0.0 {
+        let home = (p.0, values.2 as isize, values.3 as isize);
+        let tiles : Vec<(usize, isize, isize)> = if area.is_empty() {
+            // Tiles reachable from here which are inside the radius around home
+            get_reachable_tiles(instance_index, p, radius + compute_distance(&p, &home), data).into_iter().filter(|t| compute_distance(t, &home) <= radius).collect()
+        } else {
+            get_schedule_destination(&area, p.0, data).into_iter().filter(|t| t.0 == p.0).collect()
+        };
+        if tiles.is_empty() {
+            return BehaviorNodeConnector::Fail;
+        }
+        let dp = tiles[data.rng.borrow_mut().gen_range(0..tiles.len())];
+        values.0 = dp.1 as f64;
+        values.1 = dp.2 as f64;
+    }
+
+    let rc = walk_towards(instance_index, Some(p), Some((p.0, values.0 as isize, values.1 as isize)), false, data);
+
+    // Arrived or blocked, choose a new destination next time
+    if rc != BehaviorNodeConnector::Right {
+        values.0 = -1.0;
+        values.1 = -1.0;
+    }
+    data.instances[instance_index].state_values.insert(key, values);
+    rc
+}
+
+/// Flee, walks to the reachable tile farthest away from the target until the target is at least the given distance away
+pub fn flee(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let p = data.instances[instance_index].position;
+    let mut tp = None;
+    if let Some(target_index) = data.instances[instance_index].target_instance_index {
+        tp = data.instances[target_index].position;
+    }
+
+    let mut distance = 6.0;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "distance".to_string()), data) {
+        distance = rc;
+    }
+
+    if let Some(p) = p {
+        if let Some(tp) = tp {
+            // Success once we are far enough away
+            if p.0 != tp.0 || compute_distance(&p, &tp) >= distance {
+                return BehaviorNodeConnector::Success;
+            }
+
+            apply_speed_delay(instance_index, id, data, behavior_type);
+
+            // The farthest tile from the target, the closest one of those from us
+            let mut best : Option<(usize, isize, isize)> = None;
+            let mut best_distance = compute_distance(&p, &tp);
+            for tile in get_reachable_tiles(instance_index, p, distance, data) {
+                let d = compute_distance(&tile, &tp);
+                if d > best_distance || (d == best_distance && best.is_some() && compute_distance(&p, &tile) < compute_distance(&p, &best.unwrap())) {
+                    best = Some(tile);
+                    best_distance = d;
+                }
+            }
+
+            if let Some(dp) = best {
+                return walk_towards(instance_index, Some(p), Some(dp), false, data);
+            }
+            return BehaviorNodeConnector::Fail;
+        }
+        return BehaviorNodeConnector::Success;
+    }
+    BehaviorNodeConnector::Fail
+}
+
+/// Patrol, walks along the waypoints in order and starts again at the first one. Each line of the waypoints
+/// is an "x y" position in the region of the character or an area whose tiles are visited one after another.
+/// The index of the current waypoint is kept in the state values.
+pub fn patrol(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let p = match data.instances[instance_index].position {
+        Some(p) => p,
+        None => return BehaviorNodeConnector::Fail,
+    };
+
+    let mut waypoints = vec![];
+    if let Some(value) = get_node_value((id.0, id.1, "waypoints"), data, behavior_type, 0) {
+        for line in value.4.lines() {
+            let line = line.trim();
+            if line.is_empty() == false && line.starts_with("//") == false {
+                waypoints.extend(get_schedule_destination(line, p.0, data));
+            }
+        }
+    }
+
+    if waypoints.is_empty() {
+        return BehaviorNodeConnector::Fail;
+    }
+
+    apply_speed_delay(instance_index, id, data, behavior_type);
+
+    let key = format!("patrol:{}:{}", id.0, id.1);
+    let mut index = 0;
+    if let Some(value) = data.instances[instance_index].state_values.get(&key) {
+        index = value.0 as usize % waypoints.len();
+    }
+
+    let rc = walk_towards(instance_index, Some(p), Some(waypoints[index]), false, data);
+
+    // Reached or unreachable, continue with the next waypoint
+    if rc == BehaviorNodeConnector::Success || rc == BehaviorNodeConnector::Fail {
+        index = (index + 1) % waypoints.len();
+        data.instances[instance_index].state_values.insert(key, (index as f64, 0.0, 0.0, 0.0, "".to_string()));
+    }
+    rc
+}
+
+/// Schedule, walks the character to the destination of the entry active at the current time of day
+/// and executes the tree of the entry once there. The character stays locked to the tree of the
+/// schedule while an entry is active. The day of the schedule lasts "day_length" game hours and is
+/// shifted by "offset" game hours, by default it is the day of the game clock.
+pub fn schedule(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut entries = vec![];
+    if let Some(value) = get_node_value((id.0, id.1, "schedule"), data, behavior_type, 0) {
+        entries = parse_schedule(&value.4);
+    }
+
+    // The day of the schedule, by default the day of the game clock
+    let mut day_length = 24.0;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "day_length".to_string()), data) {
+        day_length = rc;
+    }
+
+    let mut offset = 0.0;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "offset".to_string()), data) {
+        offset = rc;
+    }
+
+    let minutes = get_schedule_minutes(data.clock.total_minutes(data.tick_count), day_length, offset);
+    let schedule_tree = get_behavior_tree_of_node(id.0, id.1, data);
+
+    let entry = entries.into_iter().find(|e| is_time_in_range(minutes, e.from, e.to));
+    let entry = match entry {
+        Some(entry) => entry,
+        None => {
+            // Nothing scheduled, release the character
+            if schedule_tree.is_some() && data.instances[instance_index].locked_tree == schedule_tree {
+                data.instances[instance_index].locked_tree = None;
+            }
+            return BehaviorNodeConnector::Fail;
+        }
+    };
+
+    if schedule_tree.is_some() {
+        data.instances[instance_index].locked_tree = schedule_tree;
+    }
+
+    let p = data.instances[instance_index].position;
+    if let Some(p) = p {
+        let tiles = get_schedule_destination(&entry.destination, p.0, data);
+        if tiles.is_empty() {
+            // Report an unknown destination once per character
+            let reported = data.instances[instance_index].node_values.get(&(behavior_type, id.1)).map(|v| v.4 == entry.destination).unwrap_or(false);
+            if reported == false {
+                data.messages.push((format!("{}: Unknown schedule destination \"{}\"", data.instances[instance_index].name, entry.destination), MessageType::Error));
+                data.instances[instance_index].node_values.insert((behavior_type, id.1), (0.0, 0.0, 0.0, 0.0, entry.destination.clone()));
+            }
+            return BehaviorNodeConnector::Fail;
+        }
+
+        if tiles.contains(&p) == false {
+            // Walk to the closest free tile of the destination
+            let mut dp = tiles[0];
+            let mut distance = f64::MAX;
+            if let Some(region) = data.regions.get(&dp.0) {
+                for t in &tiles {
+                    let d = if t.0 == p.0 { compute_distance(&p, t) } else { 0.0 };
+                    if d < distance && region.grid.contains((t.1, t.2)) == false {
+                        distance = d;
+                        dp = *t;
+                    }
+                }
+            }
+
+            apply_speed_delay(instance_index, id, data, behavior_type);
+
+            let rc = walk_towards(instance_index, Some(p), Some(dp), false, data);
+            if rc != BehaviorNodeConnector::Success {
+                return rc;
+            }
+        }
+
+        // Arrived, execute the tree of the entry
+        if entry.tree.is_empty() == false {
+            let mut tree_id : Option<usize> = None;
+            if let Some(behavior) = data.behaviors.get(&data.instances[instance_index].behavior_id) {
+                for (node_id, node) in &behavior.data.nodes {
+                    if node.behavior_type == BehaviorNodeType::BehaviorTree && node.name == entry.tree {
+                        tree_id = Some(*node_id);
+                        break;
+                    }
+                }
+            }
+            if let Some(tree_id) = tree_id {
+                if Some(tree_id) != schedule_tree {
+                    data.execute_node(instance_index, tree_id);
+                }
+            }
+        }
+        return BehaviorNodeConnector::Success;
+    }
+    BehaviorNodeConnector::Fail
+}
+
 /// Lookout
 pub fn lookout(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
 
@@ -129,24 +490,30 @@ pub fn lookout(instance_index: usize, id: (usize, usize), data: &mut GameData, b
         max_distance = rc;
     }
 
-    // Find the chars within the given distance
+    let mut field_of_view : f64 = 360.0;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "field_of_view".to_string()), data) {
+        field_of_view = rc;
+    }
+
+    let mut faction_filter = 0;
+    if let Some(value) = get_node_value((id.0, id.1, "faction"), data, behavior_type, 0) {
+        faction_filter = value.0 as usize;
+    }
+
+    // Find the chars within the given distance which are visible
 
     let mut chars : Vec<usize> = vec![];
 
     if let Some(position) = data.instances[instance_index].position {
-        for inst_index in 0..data.instances.len() {
+        let mut candidates = vec![];
+        if let Some(region) = data.regions.get(&position.0) {
+            candidates = region.grid.within((position.1, position.2), max_distance);
+        }
+        for inst_index in candidates {
             if inst_index != instance_index {
                 if data.instances[inst_index].state == BehaviorInstanceState::Normal {
-                    if let Some(pos) = data.instances[inst_index].position {
-                        if pos.0 == position.0 {
-                            let dx = position.1 - pos.1;
-                            let dy = position.2 - pos.2;
-                            let d = ((dx * dx + dy * dy) as f64).sqrt();
-                            if d <= max_distance {
-                                chars.push(inst_index);
-                                //println!("distance {}", d);
-                            }
-                        }
+                    if can_see(instance_index, inst_index, max_distance, field_of_view, data) && matches_faction_filter(instance_index, inst_index, faction_filter, data) {
+                        chars.push(inst_index);
                     }
                 }
             }
@@ -167,7 +534,7 @@ pub fn lookout(instance_index: usize, id: (usize, usize), data: &mut GameData, b
     BehaviorNodeConnector::Fail
 }
 
-/// CloseIn
+/// CloseIn, fails if the target does not pass the faction filter
 pub fn close_in(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
 
     let mut p : Option<(usize, isize, isize)> = None;
@@ -187,22 +554,22 @@ pub fn close_in(instance_index: usize, id: (usize, usize), data: &mut GameData,
     let target_index = data.instances[instance_index].target_instance_index;
 
     if let Some(target_index) = target_index {
+        if let Some(value) = get_node_value((id.0, id.1, "faction"), data, behavior_type, 0) {
+            if matches_faction_filter(instance_index, target_index, value.0 as usize, data) == false {
+                return BehaviorNodeConnector::Fail;
+            }
+        }
         if let Some(v) = &mut data.instances[target_index].position {
             dp = Some(*v);
             if let Some(p) = p {
-                distance = compute_distance(&p, v);
+                if p.0 == v.0 {
+                    distance = compute_distance(&p, v);
+                }
             }
         }
     }
 
-    let mut speed : f64 = 8.0;
-    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "speed".to_string()), data) {
-        speed = rc;
-    }
-
-    // Apply the speed delay
-    let delay = 10.0 - speed.clamp(0.0, 10.0);
-    data.instances[instance_index].sleep_cycles = delay as usize;
+    apply_speed_delay(instance_index, id, data, behavior_type);
 
     // Success if we reached the to_distance already
     if distance <= to_distance {
@@ -212,6 +579,111 @@ pub fn close_in(instance_index: usize, id: (usize, usize), data: &mut GameData,
     walk_towards(instance_index, p, dp, true, data)
 }
 
+/// Adjust Reputation, changes the reputation between the faction of the instance and the given faction, or the faction of the target if empty
+pub fn adjust_reputation(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut other = "".to_string();
+    if let Some(value) = get_node_value((id.0, id.1, "faction"), data, behavior_type, 0) {
+        other = value.4.trim().to_string();
+    }
+    if other.is_empty() {
+        if let Some(target_index) = data.instances[instance_index].target_instance_index {
+            other = get_faction(target_index, data);
+        }
+    }
+
+    let mut amount = 0.0;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "amount".to_string()), data) {
+        amount = rc;
+    }
+
+    let faction = get_faction(instance_index, data);
+    data.factions.adjust(&faction, &other, amount);
+
+    BehaviorNodeConnector::Bottom
+}
+
+/// Recruit, the character joins the party of its target, fails if it is already in a party or the party reached the given size
+pub fn recruit(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut max_size = 4;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "size".to_string()), data) {
+        max_size = rc.max(1.0) as usize;
+    }
+
+    if let Some(target_index) = data.instances[instance_index].target_instance_index {
+        if target_index == instance_index || get_party_leader(instance_index, data).is_some() {
+            return BehaviorNodeConnector::Fail;
+        }
+
+        // The target may be a member of another party, join the leader of that party
+        let leader = get_party_leader(target_index, data).unwrap_or(target_index);
+        if data.instances[leader].party.len().max(1) >= max_size {
+            return BehaviorNodeConnector::Fail;
+        }
+        if data.instances[leader].party.is_empty() {
+            let leader_id = data.instances[leader].id;
+            data.instances[leader].party.push(leader_id);
+        }
+
+        let member_id = data.instances[instance_index].id;
+        data.instances[leader].party.push(member_id);
+        send_status_message(leader, format!("{} joins the party.", data.instances[instance_index].name), data);
+        return BehaviorNodeConnector::Success;
+    }
+    BehaviorNodeConnector::Fail
+}
+
+/// Dismiss, the character leaves its party. A leader dismissing itself disbands the party.
+pub fn dismiss(instance_index: usize, _id: (usize, usize), data: &mut GameData, _behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    if let Some(leader) = get_party_leader(instance_index, data) {
+        if leader == instance_index {
+            data.instances[leader].party.clear();
+        } else {
+            let member_id = data.instances[instance_index].id;
+            data.instances[leader].party.retain(|id| *id != member_id);
+            send_status_message(leader, format!("{} leaves the party.", data.instances[instance_index].name), data);
+
+            // Only the leader left
+            if data.instances[leader].party.len() < 2 {
+                data.instances[leader].party.clear();
+            }
+        }
+        return BehaviorNodeConnector::Success;
+    }
+    BehaviorNodeConnector::Fail
+}
+
+/// Follow, walks towards the leader of the party until it is within the given distance, the leader may be in another region
+pub fn follow(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let leader = match get_party_leader(instance_index, data) {
+        Some(leader) if leader != instance_index => leader,
+        _ => return BehaviorNodeConnector::Fail,
+    };
+
+    let mut distance = 2.0;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "distance".to_string()), data) {
+        distance = rc;
+    }
+
+    let p = data.instances[instance_index].position;
+    let dp = data.instances[leader].position;
+
+    if let Some(p) = p {
+        if let Some(dp) = dp {
+            if p.0 == dp.0 && compute_distance(&p, &dp) <= distance {
+                return BehaviorNodeConnector::Success;
+            }
+        }
+    }
+
+    apply_speed_delay(instance_index, id, data, behavior_type);
+
+    walk_towards(instance_index, p, dp, true, data)
+}
+
 /// Systems Call
 pub fn call_system(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
 
@@ -412,6 +884,7 @@ pub fn set_state(instance_index: usize, id: (usize, usize), data: &mut GameData,
     if let Some(value) = get_node_value((id.0, id.1, "state"), data, behavior_type, 0) {
         if let Some(behavior_instance) = behavior_instance {
             //println!("behavior instance {:?}", behavior_instance);
+            let was_alive = data.instances[behavior_instance].state == BehaviorInstanceState::Normal || data.instances[behavior_instance].state == BehaviorInstanceState::Hidden;
             data.instances[behavior_instance].state = match value.0 as isize {
                 1 => BehaviorInstanceState::Hidden,
                 2 => BehaviorInstanceState::Killed,
@@ -420,6 +893,17 @@ pub fn set_state(instance_index: usize, id: (usize, usize), data: &mut GameData,
                 _ => BehaviorInstanceState::Normal,
             };
 
+            // Killing a character counts for the quests of the killer, a character killing itself credits its target
+            if was_alive && data.instances[behavior_instance].state == BehaviorInstanceState::Killed {
+                let killer = if behavior_instance == instance_index { data.instances[instance_index].target_instance_index } else { Some(instance_index) };
+                if let Some(player_index) = killer.and_then(|index| get_quest_owner(index, data)) {
+                    if let Some(behavior) = data.behaviors.get(&data.instances[behavior_instance].behavior_id) {
+                        let name = behavior.name.clone();
+                        quest_killed(player_index, &name, data);
+                    }
+                }
+            }
+
             // If != normal, clean this instance from all targets
             if data.instances[behavior_instance].state != BehaviorInstanceState::Normal {
                 for i in 0..data.instances.len() {
@@ -438,14 +922,7 @@ pub fn set_state(instance_index: usize, id: (usize, usize), data: &mut GameData,
 /// Player: Move
 pub fn player_move(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
 
-    let mut speed : f64 = 8.0;
-    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "speed".to_string()), data) {
-        speed = rc;
-    }
-
-    // Apply the speed delay
-    let delay = 10.0 - speed.clamp(0.0, 10.0);
-    data.instances[instance_index].sleep_cycles = delay as usize;
+    apply_speed_delay(instance_index, id, data, behavior_type);
 
     let mut dp:Option<(usize, isize, isize)> = None;
     if let Some(p) = data.instances[instance_index].position {
@@ -474,3 +951,164 @@ pub fn player_move(instance_index: usize, id: (usize, usize), data: &mut GameDat
     //println!("rc {:?}", rc);
     rc
 }
+
+/// Player take, takes an item (action text, or any item if empty) from the tile in the action direction or below the player
+pub fn player_take(instance_index: usize, _id: (usize, usize), data: &mut GameData, _behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut rc = BehaviorNodeConnector::Fail;
+
+    if let Some(position) = get_action_position(instance_index, data) {
+        let mut name = "".to_string();
+        if let Some(action) = &data.instances[instance_index].action {
+            name = action.text.clone();
+        }
+
+        let mut taken = None;
+        if let Some(items) = data.loot.get_mut(&position) {
+            if let Some(index) = items.iter().position(|item| name.is_empty() || item.name.eq_ignore_ascii_case(&name)) {
+                taken = Some(items.remove(index));
+            }
+            if items.is_empty() {
+                data.loot.remove(&position);
+            }
+        }
+
+        if let Some(item) = taken {
+            send_status_message(instance_index, format!("You take the {}.", item.name), data);
+            data.instances[instance_index].inventory.push(item);
+            rc = BehaviorNodeConnector::Success;
+        }
+    }
+
+    data.instances[instance_index].action = None;
+    rc
+}
+
+/// Player drop, drops the item of the action text from the inventory
+pub fn player_drop(instance_index: usize, _id: (usize, usize), data: &mut GameData, _behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut rc = BehaviorNodeConnector::Fail;
+
+    if let Some(position) = data.instances[instance_index].position {
+        if let Some(index) = get_action_inventory_index(instance_index, data) {
+            let item = data.instances[instance_index].inventory.remove(index);
+            send_status_message(instance_index, format!("You drop the {}.", item.name), data);
+            data.loot.entry(position).or_insert(vec![]).push(item);
+            rc = BehaviorNodeConnector::Success;
+        }
+    }
+
+    data.instances[instance_index].action = None;
+    rc
+}
+
+/// Player talk, executes the "onTalk" tree of the character in the action direction or next to the player
+pub fn player_talk(instance_index: usize, _id: (usize, usize), data: &mut GameData, _behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let partner = get_talk_partner(instance_index, data);
+    data.instances[instance_index].action = None;
+
+    if let Some(npc_index) = partner {
+        let mut tree_id : Option<usize> = None;
+        if let Some(behavior) = data.behaviors.get(&data.instances[npc_index].behavior_id) {
+            for id in &data.instances[npc_index].tree_ids {
+                if let Some(node) = behavior.data.nodes.get(id) {
+                    if node.name == "onTalk" {
+                        tree_id = Some(*id);
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some(tree_id) = tree_id {
+            data.instances[instance_index].dialog = None;
+            data.instances[instance_index].target_instance_index = Some(npc_index);
+            data.instances[npc_index].target_instance_index = Some(instance_index);
+            let name = data.instances[npc_index].name.clone();
+            quest_talked_to(instance_index, &name, data);
+            data.execute_node(npc_index, tree_id);
+            return BehaviorNodeConnector::Success;
+        }
+
+        send_status_message(instance_index, format!("{} has nothing to say.", data.instances[npc_index].name), data);
+        return BehaviorNodeConnector::Fail;
+    }
+
+    send_status_message(instance_index, "There is nobody to talk to.".to_string(), data);
+    BehaviorNodeConnector::Fail
+}
+
+/// Dialog, shows the text and the choices to the player the character talks to. Execution stops here, once the
+/// player picks a choice the nodes connected to the connector of the choice are executed. A choice is only shown
+/// if its condition, evaluated in the scope of the character, is empty or true.
+pub fn dialog(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let player_index;
+    if let Some(target_index) = data.instances[instance_index].target_instance_index {
+        if data.instances[target_index].instance_type != BehaviorInstanceType::Player {
+            return BehaviorNodeConnector::Fail;
+        }
+        player_index = target_index;
+    } else {
+        return BehaviorNodeConnector::Fail;
+    }
+
+    let mut text = "".to_string();
+    if let Some(value) = get_node_value((id.0, id.1, "text"), data, behavior_type, 0) {
+        text = interpolate_text(instance_index, value.4, data);
+    }
+
+    let mut choices = vec![];
+    let mut connectors = vec![];
+    let choice_connectors = [BehaviorNodeConnector::Bottom1, BehaviorNodeConnector::Bottom2, BehaviorNodeConnector::Bottom3, BehaviorNodeConnector::Bottom4];
+    for (index, connector) in choice_connectors.iter().enumerate() {
+        if let Some(value) = get_node_value((id.0, id.1, format!("choice{}", index + 1).as_str()), data, behavior_type, 0) {
+            if value.4.trim().is_empty() == false && is_dialog_choice_available(instance_index, id, index + 1, data, behavior_type) {
+                choices.push(interpolate_text(instance_index, value.4, data));
+                connectors.push(*connector);
+            }
+        }
+    }
+
+    if choices.is_empty() {
+        choices.push("Continue".to_string());
+        connectors.push(BehaviorNodeConnector::Bottom1);
+    }
+
+    let from = data.instances[instance_index].name.clone();
+    data.instances[player_index].dialog = Some(Dialog { npc_index: instance_index, node_id: id.1, connectors, data: DialogData { from, text, choices } });
+
+    BehaviorNodeConnector::Right
+}
+
+/// Player use item, executes the "onUse" tree of the item of the action text
+pub fn player_use_item(instance_index: usize, _id: (usize, usize), data: &mut GameData, _behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut item_id : Option<usize> = None;
+    let mut tree_id : Option<usize> = None;
+
+    if let Some(index) = get_action_inventory_index(instance_index, data) {
+        let id = data.instances[instance_index].inventory[index].item_id;
+        if let Some(item) = data.items.get(&id) {
+            for (node_id, node) in item.data.nodes.iter().sorted_by_key(|(id, _)| **id) {
+                if node.behavior_type == BehaviorNodeType::BehaviorTree && node.name == "onUse" {
+                    tree_id = Some(*node_id);
+                    break;
+                }
+            }
+        }
+        item_id = Some(id);
+    }
+
+    data.instances[instance_index].action = None;
+
+    if let Some(item_id) = item_id {
+        if let Some(tree_id) = tree_id {
+            data.execute_item_node(instance_index, item_id, tree_id);
+            return BehaviorNodeConnector::Success;
+        }
+    }
+
+    BehaviorNodeConnector::Fail
+}
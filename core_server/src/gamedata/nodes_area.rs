@@ -1,17 +1,38 @@
 use crate::gamedata::behavior::{ BehaviorNodeConnector };
 use crate::gamedata::GameData;
 
-use super::behavior::{ BehaviorType };
+use super::behavior::{ BehaviorType, BehaviorInstanceState, BehaviorInstanceType };
 use crate::gamedata::get_node_value;
 use core_shared::asset::TileUsage;
 use core_shared::light::Light;
 use core_shared::message::{MessageType, MessageData};
 
+use rand::Rng;
+
 /// Always
 pub fn always(_region_id: usize, _id: (usize, usize), _data: &mut GameData, _behavior_type: BehaviorType) -> BehaviorNodeConnector {
     BehaviorNodeConnector::Right
 }
 
+/// Returns true if an Enter Area or Inside Area trigger with the given "applies_to" value fires for characters of the instance type
+pub fn area_trigger_applies(applies_to: usize, instance_type: BehaviorInstanceType) -> bool {
+    match applies_to {
+        1 => instance_type == BehaviorInstanceType::Player,
+        2 => instance_type == BehaviorInstanceType::NonPlayerCharacter,
+        _ => true
+    }
+}
+
+/// Returns the instances in the area the trigger applies to
+fn get_triggering_instances(region_id: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> Vec<usize> {
+    let mut applies_to = 0;
+    if let Some(value) = get_node_value((id.0, id.1, "applies_to"), data, behavior_type, region_id) {
+        applies_to = value.0 as usize;
+    }
+
+    data.get_instances_in_area(region_id, id.0).into_iter().filter(|index| area_trigger_applies(applies_to, data.instances[*index].instance_type)).collect()
+}
+
 /// Enter Area
 pub fn enter_area(region_id: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
 
@@ -24,43 +45,31 @@ pub fn enter_area(region_id: usize, id: (usize, usize), data: &mut GameData, beh
     }
 
     let mut found_character = false;
-    if let Some(region) = data.regions.get_mut(&region_id) {
-        if let Some(characters) = data.characters.get(&region_id) {
-            for character_data in characters {
-                if let Some(position) = data.instances[character_data.index].position {
-                    if region.data.areas[id.0].area.contains(&(position.1, position.2)) {
-
-                        if data.area_characters.contains_key(&(region_id, id.0)) == false {
-                            data.area_characters.insert((region_id, id.0), vec![character_data.index]);
-                        } else
-                        if let Some(area_list) = data.area_characters.get_mut(&(region_id, id.0)) {
-                            if area_list.contains(&character_data.index) == false {
-                                area_list.push(character_data.index);
-                            }
-                        }
+    for index in get_triggering_instances(region_id, id, data, behavior_type) {
+
+        if data.area_characters.contains_key(&(region_id, id.0)) == false {
+            data.area_characters.insert((region_id, id.0), vec![index]);
+        } else
+        if let Some(area_list) = data.area_characters.get_mut(&(region_id, id.0)) {
+            if area_list.contains(&index) == false {
+                area_list.push(index);
+            }
+        }
 
-                        // Check if the character existed already in the area in the previous tick
-                        let mut was_inside_already = false;
-                        if let Some(area_list) = data.prev_area_characters.get(&(region_id, id.0)) {
-                            for index in area_list {
-                                if *index == character_data.index {
-                                    was_inside_already = true;
-                                }
-                            }
-                        }
+        // Check if the character existed already in the area in the previous tick
+        let mut was_inside_already = false;
+        if let Some(area_list) = data.prev_area_characters.get(&(region_id, id.0)) {
+            was_inside_already = area_list.contains(&index);
+        }
 
-                        if was_inside_already == false {
-                            if enter_everyone {
-                                // Trigger always if somebody enters
-                                found_character = true;
-                            } else
-                            if data.prev_area_characters.contains_key(&(region_id, id.0)) == false {
-                                // This area was empty in the previous tick
-                                found_character = true;
-                            }
-                        }
-                    }
-                }
+        if was_inside_already == false {
+            if enter_everyone {
+                // Trigger always if somebody enters
+                found_character = true;
+            } else
+            if data.prev_area_characters.contains_key(&(region_id, id.0)) == false {
+                // This area was empty in the previous tick
+                found_character = true;
             }
         }
     }
@@ -137,28 +146,19 @@ pub fn leave_area(region_id: usize, id: (usize, usize), data: &mut GameData, beh
 }
 
 /// Inside Area
-pub fn inside_area(region_id: usize, id: (usize, usize), data: &mut GameData, _behavior_type: BehaviorType) -> BehaviorNodeConnector {
+pub fn inside_area(region_id: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
 
     let mut found_character = false;
-    if let Some(region) = data.regions.get_mut(&region_id) {
-        if let Some(characters) = data.characters.get(&region_id) {
-            for character_data in characters {
-                if let Some(position) = data.instances[character_data.index].position {
-                    if region.data.areas[id.0].area.contains(&(position.1, position.2)) {
-                        //println!("{} is in area {}", data.instances[*instance_index].name, region.data.areas[id.0].name);
-                        if data.area_characters.contains_key(&(region_id, id.0)) == false {
-                            data.area_characters.insert((region_id, id.0), vec![character_data.index]);
-                        } else
-                        if let Some(area_list) = data.area_characters.get_mut(&(region_id, id.0)) {
-                            if area_list.contains(&character_data.index) == false {
-                                area_list.push(character_data.index);
-                            }
-                        }
-                        found_character = true;
-                    }
-                }
+    for index in get_triggering_instances(region_id, id, data, behavior_type) {
+        if data.area_characters.contains_key(&(region_id, id.0)) == false {
+            data.area_characters.insert((region_id, id.0), vec![index]);
+        } else
+        if let Some(area_list) = data.area_characters.get_mut(&(region_id, id.0)) {
+            if area_list.contains(&index) == false {
+                area_list.push(index);
             }
         }
+        found_character = true;
     }
 
     if found_character {
@@ -210,8 +210,10 @@ pub fn teleport_area(region_id: usize, id: (usize, usize), data: &mut GameData,
     // Somebody is in the area ?
     if let Some(area_list) = data.area_characters.get(&(region_id, id.0)) {
         if let Some(value) = value {
-            for index in area_list {
-                data.instances[*index].position = Some((value.0 as usize, value.1 as isize, value.2 as isize));
+            for index in area_list.clone() {
+                let old_position = data.instances[index].position;
+                data.instances[index].position = Some((value.0 as usize, value.1 as isize, value.2 as isize));
+                data.update_instance_position(index, old_position);
             }
         }
     }
@@ -299,4 +301,132 @@ pub fn light_area(region_id: usize, id: (usize, usize), data: &mut GameData, _be
     }
 
     BehaviorNodeConnector::Fail
-}
\ No newline at end of file
+}
+
+/// Spawn, creates instances of the given behavior at the position or inside the area, up to the given maximum of living spawns
+pub fn spawn_area(region_id: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut behavior_id : Option<usize> = None;
+    if let Some(value) = get_node_value((id.0, id.1, "behavior"), data, behavior_type, region_id) {
+        if let Some(index) = data.behaviors_names.iter().position(|name| *name == value.4) {
+            behavior_id = Some(data.behaviors_ids[index]);
+        }
+    }
+
+    let behavior_id = match behavior_id {
+        Some(behavior_id) => behavior_id,
+        None => return BehaviorNodeConnector::Fail
+    };
+
+    let mut maximum : usize = 1;
+    if let Some(value) = get_node_value((id.0, id.1, "maximum"), data, behavior_type, region_id) {
+        maximum = value.0.max(0.0) as usize;
+    }
+
+    let mut delay : usize = 0;
+    if let Some(value) = get_node_value((id.0, id.1, "delay"), data, behavior_type, region_id) {
+        delay = value.0.max(0.0) as usize;
+    }
+
+    let mut position : Option<(usize, isize, isize)> = None;
+    if let Some(value) = get_node_value((id.0, id.1, "position"), data, behavior_type, region_id) {
+        if value.0 >= 0.0 {
+            position = Some((value.0 as usize, value.1 as isize, value.2 as isize));
+        }
+    }
+
+    let tick_count = data.tick_count;
+    let mut spawn = data.spawns.remove(&(region_id, id.0, id.1)).unwrap_or_default();
+
+    // Remove the spawns which died since the last tick and start the respawn timer
+    let living = spawn.instances.len();
+    for index in spawn.instances.clone() {
+        if data.instances[index].state != BehaviorInstanceState::Normal && data.instances[index].state != BehaviorInstanceState::Hidden {
+            spawn.dead.push(index);
+        }
+    }
+    spawn.instances.retain(|index| spawn.dead.contains(index) == false);
+    if spawn.instances.len() < living {
+        spawn.next_spawn_tick = tick_count + delay;
+    }
+
+    let mut rc = BehaviorNodeConnector::Fail;
+
+    if spawn.instances.len() < maximum && tick_count >= spawn.next_spawn_tick {
+
+        // No fixed position, pick a random free tile inside the area
+        if position.is_none() {
+            let mut free_tiles : Vec<(isize, isize)> = vec![];
+            if let Some(region) = data.regions.get(&region_id) {
+                for (x, y) in &region.data.areas[id.0].area {
+                    let tiles = region.get_value((*x, *y));
+                    if tiles.is_empty() || tiles.iter().any(|tile| tile.3 == TileUsage::EnvBlocking || tile.3 == TileUsage::Water) {
+                        continue;
+                    }
+                    let occupied = region.grid.at((*x, *y)).iter().any(|index| data.instances[*index].state == BehaviorInstanceState::Normal);
+                    if occupied == false {
+                        free_tiles.push((*x, *y));
+                    }
+                }
+            }
+            if free_tiles.is_empty() == false {
+                let index = data.rng.borrow_mut().gen_range(0..free_tiles.len());
+                position = Some((region_id, free_tiles[index].0, free_tiles[index].1));
+            }
+        }
+
+        if let Some(position) = position {
+            // Reuse the slot of a dead spawn, otherwise the instances grow with every respawn
+            spawn.dead.retain(|index| data.instances[*index].state == BehaviorInstanceState::Killed || data.instances[*index].state == BehaviorInstanceState::Purged);
+            let index = match spawn.dead.pop() {
+                Some(dead) => data.recreate_behavior_instance(behavior_id, dead),
+                None => data.create_behavior_instance(behavior_id),
+            };
+            data.instances[index].position = Some(position);
+            data.update_instance_position(index, None);
+            spawn.instances.push(index);
+            rc = BehaviorNodeConnector::Right;
+        }
+    }
+
+    data.spawns.insert((region_id, id.0, id.1), spawn);
+
+    rc
+}
+
+#[cfg(all(test, not(feature = "embed_binaries")))]
+mod tests {
+    use super::*;
+    use crate::gamedata::behavior::BehaviorNodeType;
+
+    #[test]
+    fn respawns_reuse_dead_instances() {
+        let mut data = GameData::load_from_path(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."));
+        data.startup();
+
+        let region_id = 542323;
+        let node_id = {
+            let region = data.regions.get_mut(&region_id).unwrap();
+            let id = region.behaviors[0].add_node(BehaviorNodeType::Spawn, "Spawn".to_string());
+            let values = &mut region.behaviors[0].data.nodes.get_mut(&id).unwrap().values;
+            values.insert("behavior".to_string(), (0.0, 0.0, 0.0, 0.0, "Orc".to_string()));
+            values.insert("position".to_string(), (region_id as f64, 23.0, 3.0, 0.0, "".to_string()));
+            id
+        };
+
+        let mut count = 0;
+        for _ in 0..20 {
+            assert!(spawn_area(region_id, (0, node_id), &mut data, BehaviorType::Regions) == BehaviorNodeConnector::Right);
+            if count == 0 {
+                count = data.instances.len();
+            }
+            assert_eq!(data.instances.len(), count);
+            assert_eq!(data.scopes.len(), count);
+
+            let index = data.spawns[&(region_id, 0, node_id)].instances[0];
+            assert!(data.instances[index].state == BehaviorInstanceState::Normal);
+            assert_eq!(data.instances[index].position, Some((region_id, 23, 3)));
+            data.instances[index].state = BehaviorInstanceState::Killed;
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use crate::gamedata::behavior::{ BehaviorNodeConnector, BehaviorNodeType };
+use crate::gamedata::GameData;
+
+use super::behavior::{ BehaviorType };
+use super::nodes_utility::get_node_value;
+use super::script::eval_number_expression_instance;
+
+use rand::Rng;
+
+/// The connectors of the children of control nodes from left to right, the order the children are executed in
+const CHILD_CONNECTORS : [BehaviorNodeConnector; 5] = [BehaviorNodeConnector::Bottom1, BehaviorNodeConnector::Bottom2, BehaviorNodeConnector::Bottom, BehaviorNodeConnector::Bottom3, BehaviorNodeConnector::Bottom4];
+
+/// Selector, executes the children in order until one of them succeeds
+pub fn selector(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+    for child in get_children(id, data, behavior_type) {
+        let rc = execute_child(instance_index, id, child, data, behavior_type);
+        if rc == Some(BehaviorNodeConnector::Right) {
+            return BehaviorNodeConnector::Right;
+        }
+        if is_success(rc) {
+            return BehaviorNodeConnector::Success;
+        }
+    }
+    BehaviorNodeConnector::Fail
+}
+
+/// Inverter, fails if the children succeed and succeeds if they fail
+pub fn inverter(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+    match execute_children(instance_index, id, data, behavior_type) {
+        BehaviorNodeConnector::Success => BehaviorNodeConnector::Fail,
+        BehaviorNodeConnector::Fail => BehaviorNodeConnector::Success,
+        rc => rc,
+    }
+}
+
+/// Repeat, executes the children the given number of times. In the "Until Fail" mode the children are
+/// repeated until they fail and the count limits the repetitions per tick.
+pub fn repeat(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut until_fail = false;
+    if let Some(value) = get_node_value((id.0, id.1, "mode"), data, behavior_type, 0) {
+        until_fail = value.0 == 1.0;
+    }
+
+    let mut count = 1;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "count".to_string()), data) {
+        count = rc.max(0.0) as usize;
+    }
+
+    for _ in 0..count {
+        let rc = execute_children(instance_index, id, data, behavior_type);
+        if rc == BehaviorNodeConnector::Right {
+            return rc;
+        }
+        if rc == BehaviorNodeConnector::Fail {
+            if until_fail {
+                return BehaviorNodeConnector::Success;
+            }
+            return rc;
+        }
+    }
+
+    if until_fail {
+        BehaviorNodeConnector::Right
+    } else {
+        BehaviorNodeConnector::Success
+    }
+}
+
+/// Parallel, executes all children. Succeeds if all of them or, depending on the policy, one of them succeeded.
+pub fn parallel(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut one_succeeds = false;
+    if let Some(value) = get_node_value((id.0, id.1, "policy"), data, behavior_type, 0) {
+        one_succeeds = value.0 == 1.0;
+    }
+
+    let children = get_children(id, data, behavior_type);
+    let mut succeeded = 0;
+    let mut running = false;
+
+    for child in &children {
+        let rc = execute_child(instance_index, id, *child, data, behavior_type);
+        if rc == Some(BehaviorNodeConnector::Right) {
+            running = true;
+        } else
+        if is_success(rc) {
+            succeeded += 1;
+        }
+    }
+
+    if (one_succeeds && succeeded > 0) || (one_succeeds == false && succeeded == children.len()) {
+        BehaviorNodeConnector::Success
+    } else
+    if running {
+        BehaviorNodeConnector::Right
+    } else {
+        BehaviorNodeConnector::Fail
+    }
+}
+
+/// Cooldown, executes the children and fails until the given amount of ticks has passed.
+/// The tick of the last execution is stored per instance in the node values, ticks spent in a suspended region do not count.
+pub fn cooldown(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut ticks = 10;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "ticks".to_string()), data) {
+        ticks = rc.max(0.0) as usize;
+    }
+
+    let tick_count = data.tick_count - data.instances[instance_index].suspended_ticks;
+
+    if let Some(value) = data.instances[instance_index].node_values.get(&(behavior_type, id.1)) {
+        if tick_count < value.0 as usize + ticks {
+            return BehaviorNodeConnector::Fail;
+        }
+    }
+
+    let rc = execute_children(instance_index, id, data, behavior_type);
+
+    // The cooldown starts once the children are done
+    if rc != BehaviorNodeConnector::Right {
+        data.instances[instance_index].node_values.insert((behavior_type, id.1), (tick_count as f64, 0.0, 0.0, 0.0, "".to_string()));
+    }
+    rc
+}
+
+/// Random, executes one of the connected children. The weights are given in the order of the connectors, left to right.
+pub fn random(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+
+    let mut weights = vec![1.0; CHILD_CONNECTORS.len()];
+    if let Some(value) = get_node_value((id.0, id.1, "weights"), data, behavior_type, 0) {
+        for (index, w) in value.4.split(',').enumerate() {
+            if index < weights.len() {
+                if let Some(w) = w.trim().parse::<f64>().ok() {
+                    weights[index] = w.max(0.0);
+                }
+            }
+        }
+    }
+
+    // The weights of the connectors which have children
+    let mut candidates = vec![];
+    let mut total = 0.0;
+    for (index, connector) in CHILD_CONNECTORS.iter().enumerate() {
+        let w = weights[index];
+        if w > 0.0 && data.get_connected_nodes(behavior_type, id.0, id.1, *connector).is_empty() == false {
+            candidates.push((*connector, w));
+            total += w;
+        }
+    }
+
+    if candidates.is_empty() {
+        return BehaviorNodeConnector::Fail;
+    }
+
+    let mut choice = data.rng.borrow_mut().gen_range(0.0..total);
+    let mut connector = candidates[candidates.len() - 1].0;
+    for (c, w) in &candidates {
+        if choice < *w {
+            connector = *c;
+            break;
+        }
+        choice -= w;
+    }
+
+    let mut rc = BehaviorNodeConnector::Success;
+    for node_id in data.get_connected_nodes(behavior_type, id.0, id.1, connector) {
+        let child_rc = execute_child(instance_index, id, (connector, node_id), data, behavior_type);
+        if is_success(child_rc) == false {
+            rc = child_rc.unwrap();
+            break;
+        }
+    }
+    rc
+}
+
+/// The connected children of the node in execution order
+fn get_children(id: (usize, usize), data: &GameData, behavior_type: BehaviorType) -> Vec<(BehaviorNodeConnector, usize)> {
+    let mut rc = vec![];
+    for connector in CHILD_CONNECTORS {
+        for node_id in data.get_connected_nodes(behavior_type, id.0, id.1, connector) {
+            rc.push((connector, node_id));
+        }
+    }
+    rc
+}
+
+/// Executes the children in order like a sequence, returns Success, Fail or Right
+fn execute_children(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+    for child in get_children(id, data, behavior_type) {
+        let rc = execute_child(instance_index, id, child, data, behavior_type);
+        if is_success(rc) == false {
+            return rc.unwrap();
+        }
+    }
+    BehaviorNodeConnector::Success
+}
+
+/// Executes a single child and marks its connection as executed.
+/// A Sequence child does not report a result when executed on its own, here it reports the result of its children.
+fn execute_child(instance_index: usize, id: (usize, usize), child: (BehaviorNodeConnector, usize), data: &mut GameData, behavior_type: BehaviorType) -> Option<BehaviorNodeConnector> {
+    data.executed_connections.push((behavior_type, id.1, child.0));
+    if data.get_node_type(behavior_type, id.0, child.1) == Some(BehaviorNodeType::Sequence) {
+        return Some(execute_children(instance_index, (id.0, child.1), data, behavior_type));
+    }
+    data.execute_child_node(behavior_type, instance_index, id.0, child.1)
+}
+
+/// Children which do not report a result count as successful, Right marks a still running child
+fn is_success(rc: Option<BehaviorNodeConnector>) -> bool {
+    rc != Some(BehaviorNodeConnector::Fail) && rc != Some(BehaviorNodeConnector::Right)
+}
+
+#[cfg(all(test, not(feature = "embed_binaries")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_children_report_their_result() {
+        let mut data = GameData::load_from_path(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."));
+        data.startup();
+
+        let instance_index = data.instances.iter().position(|instance| instance.name == "Orc").unwrap();
+        let behavior_id = data.instances[instance_index].behavior_id;
+
+        // Selector -> (Sequence -> failing Inverter, empty Sequence)
+        let (selector_id, sequence_id) = {
+            let behavior = data.behaviors.get_mut(&behavior_id).unwrap();
+            let selector_id = behavior.add_node(BehaviorNodeType::Selector, "Selector".to_string());
+            let sequence_id = behavior.add_node(BehaviorNodeType::Sequence, "Sequence".to_string());
+            let failing_id = behavior.add_node(BehaviorNodeType::Inverter, "Inverter".to_string());
+            let empty_id = behavior.add_node(BehaviorNodeType::Sequence, "Sequence".to_string());
+            behavior.data.connections.push((selector_id, BehaviorNodeConnector::Bottom1, sequence_id, BehaviorNodeConnector::Top));
+            behavior.data.connections.push((sequence_id, BehaviorNodeConnector::Bottom1, failing_id, BehaviorNodeConnector::Top));
+            behavior.data.connections.push((selector_id, BehaviorNodeConnector::Bottom2, empty_id, BehaviorNodeConnector::Top));
+            (selector_id, sequence_id)
+        };
+
+        // Executed as a tree node the sequence keeps not reporting a result
+        assert!(data.execute_node(instance_index, sequence_id) == None);
+
+        // Below a control node the failing sequence makes the selector try the next child
+        data.executed_connections.clear();
+        assert!(selector(instance_index, (behavior_id, selector_id), &mut data, BehaviorType::Behaviors) == BehaviorNodeConnector::Success);
+        assert!(data.executed_connections.contains(&(BehaviorType::Behaviors, sequence_id, BehaviorNodeConnector::Bottom1)));
+        assert!(data.executed_connections.contains(&(BehaviorType::Behaviors, selector_id, BehaviorNodeConnector::Bottom2)));
+    }
+}
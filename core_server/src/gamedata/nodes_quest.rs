@@ -0,0 +1,335 @@
+use crate::gamedata::behavior::{ BehaviorNode, BehaviorNodeConnector, BehaviorInstanceType };
+use crate::gamedata::GameData;
+
+use core_shared::quest::QuestData;
+
+use super::behavior::{ BehaviorType };
+use super::nodes_utility::{ get_node_value, send_status_message };
+
+/// The objective of a quest stage
+#[derive(PartialEq, Clone, Debug)]
+pub enum QuestObjective {
+    /// Only advanced by an Advance Quest node
+    None,
+    /// Reach the area of the given name
+    Area(String),
+    /// Talk to the character of the given name
+    Talk(String),
+    /// Kill the given amount of characters of the behavior
+    Kill(usize, String),
+    /// Carry the item of the given name
+    Item(String),
+}
+
+/// A stage of a quest, "kill 3 Goblin, Kill the goblins in the forest"
+#[derive(Clone, Debug)]
+pub struct QuestStage {
+    pub objective               : QuestObjective,
+    pub description             : String,
+}
+
+/// A quest defined by a Quest node of the game behavior, the name of the node is the name of the quest
+#[derive(Clone, Debug)]
+pub struct QuestDefinition {
+    pub name                    : String,
+    pub description             : String,
+    pub stages                  : Vec<QuestStage>,
+}
+
+/// Start Quest, adds the quest to the journal of the player
+pub fn start_quest(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+    if let Some((player_index, quest)) = get_node_quest(instance_index, id, data, behavior_type) {
+        if data.instances[player_index].state_values.contains_key(&get_quest_key(&quest.name)) == false {
+            data.instances[player_index].state_values.insert(get_quest_key(&quest.name), (0.0, 0.0, 0.0, 0.0, "".to_string()));
+            send_status_message(player_index, format!("New quest: {}.", quest.name), data);
+            update_quest(player_index, &quest, data);
+            return BehaviorNodeConnector::Success;
+        }
+    }
+    BehaviorNodeConnector::Fail
+}
+
+/// Advance Quest, moves the active quest of the player to the next stage, the last stage completes the quest
+pub fn advance_quest(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+    if let Some((player_index, quest)) = get_node_quest(instance_index, id, data, behavior_type) {
+        if is_quest_active(player_index, &quest.name, data) {
+            next_quest_stage(player_index, &quest, data);
+            update_quest(player_index, &quest, data);
+            return BehaviorNodeConnector::Success;
+        }
+    }
+    BehaviorNodeConnector::Fail
+}
+
+/// Complete Quest, completes the active quest of the player regardless of its stage
+pub fn complete_quest(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> BehaviorNodeConnector {
+    if let Some((player_index, quest)) = get_node_quest(instance_index, id, data, behavior_type) {
+        if is_quest_active(player_index, &quest.name, data) {
+            set_quest_completed(player_index, &quest, data);
+            return BehaviorNodeConnector::Success;
+        }
+    }
+    BehaviorNodeConnector::Fail
+}
+
+/// Parses the stages of a Quest node, one stage per line, lines starting with // are ignored
+pub fn parse_quest_stages(text: &str) -> Vec<QuestStage> {
+    let mut rc = vec![];
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        let (objective, description) = line.split_once(',').unwrap_or((line, ""));
+        let mut parts = objective.trim().splitn(2, ' ');
+        let kind = parts.next().unwrap_or("").to_lowercase();
+        let arg = parts.next().unwrap_or("").trim().to_string();
+
+        let objective = match kind.as_str() {
+            "area" => QuestObjective::Area(arg),
+            "talk" => QuestObjective::Talk(arg),
+            "item" => QuestObjective::Item(arg),
+            "kill" => {
+                let (count, behavior) = arg.split_once(' ').unwrap_or(("1", arg.as_str()));
+                if let Some(count) = count.parse::<usize>().ok() {
+                    QuestObjective::Kill(count, behavior.trim().to_string())
+                } else {
+                    QuestObjective::Kill(1, arg.clone())
+                }
+            },
+            _ => QuestObjective::None,
+        };
+        rc.push(QuestStage { objective, description: description.trim().to_string() });
+    }
+    rc
+}
+
+/// Creates the quest of a Quest node of the game behavior
+pub fn parse_quest(node: &BehaviorNode) -> QuestDefinition {
+    let mut description = "".to_string();
+    if let Some(value) = node.values.get("description") {
+        description = value.4.clone();
+    }
+    let mut stages = vec![];
+    if let Some(value) = node.values.get("stages") {
+        stages = parse_quest_stages(&value.4);
+    }
+    QuestDefinition { name: node.name.clone(), description, stages }
+}
+
+/// Returns the quest of the given name
+pub fn get_quest(name: &str, data: &GameData) -> Option<QuestDefinition> {
+    data.quests.iter().find(|quest| quest.name.eq_ignore_ascii_case(name)).cloned()
+}
+
+/// The player a quest node refers to, the instance itself if it is a player, otherwise its target
+pub fn get_quest_owner(instance_index: usize, data: &GameData) -> Option<usize> {
+    if data.instances[instance_index].instance_type == BehaviorInstanceType::Player {
+        return Some(instance_index);
+    }
+    if let Some(target_index) = data.instances[instance_index].target_instance_index {
+        if data.instances[target_index].instance_type == BehaviorInstanceType::Player {
+            return Some(target_index);
+        }
+    }
+    None
+}
+
+/// Counts a talk with the given character for the active quests of the player
+pub fn quest_talked_to(player_index: usize, name: &str, data: &mut GameData) {
+    for quest in get_active_quests(player_index, data) {
+        if let Some(stage) = get_quest_stage(player_index, &quest, data) {
+            if let QuestObjective::Talk(npc) = &stage.objective {
+                if npc.eq_ignore_ascii_case(name) {
+                    add_quest_progress(player_index, &quest, data);
+                }
+            }
+        }
+    }
+}
+
+/// Counts a kill of a character of the given behavior for the active quests of the player
+pub fn quest_killed(player_index: usize, behavior_name: &str, data: &mut GameData) {
+    for quest in get_active_quests(player_index, data) {
+        if let Some(stage) = get_quest_stage(player_index, &quest, data) {
+            if let QuestObjective::Kill(_, behavior) = &stage.objective {
+                if behavior.eq_ignore_ascii_case(behavior_name) {
+                    add_quest_progress(player_index, &quest, data);
+                }
+            }
+        }
+    }
+}
+
+/// Checks the objectives of the active quests of the player and advances the quests whose current stage is done
+pub fn update_quests(player_index: usize, data: &mut GameData) {
+    for quest in get_active_quests(player_index, data) {
+        update_quest(player_index, &quest, data);
+    }
+}
+
+/// The journal of the player, sorted by quest name
+pub fn get_journal(player_index: usize, data: &GameData) -> Vec<QuestData> {
+    let mut journal = vec![];
+    let mut keys : Vec<&String> = data.instances[player_index].state_values.keys().filter(|key| key.starts_with("quest:")).collect();
+    keys.sort();
+
+    for key in keys {
+        if let Some(quest) = get_quest(&key[6..], data) {
+            let value = &data.instances[player_index].state_values[key];
+            let completed = value.2 == 1.0;
+            let mut objective = "".to_string();
+            let mut required = 0;
+            if completed == false {
+                if let Some(stage) = quest.stages.get(value.0 as usize) {
+                    objective = stage.description.clone();
+                    required = get_required_progress(&stage.objective);
+                }
+            }
+            journal.push(QuestData { name: quest.name, description: quest.description, objective, progress: value.1 as usize, required, completed });
+        }
+    }
+    journal
+}
+
+/// The state value key of the quest, the value holds the stage, the progress of the stage and 1 if completed
+fn get_quest_key(name: &str) -> String {
+    format!("quest:{}", name)
+}
+
+/// The player and the quest of the "quest" value of the node
+fn get_node_quest(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) -> Option<(usize, QuestDefinition)> {
+    let mut name = "".to_string();
+    if let Some(value) = get_node_value((id.0, id.1, "quest"), data, behavior_type, 0) {
+        name = value.4;
+    }
+
+    if let Some(player_index) = get_quest_owner(instance_index, data) {
+        if let Some(quest) = get_quest(&name, data) {
+            return Some((player_index, quest));
+        }
+    }
+    None
+}
+
+fn is_quest_active(player_index: usize, name: &str, data: &GameData) -> bool {
+    if let Some(value) = data.instances[player_index].state_values.get(&get_quest_key(name)) {
+        return value.2 != 1.0;
+    }
+    false
+}
+
+fn get_active_quests(player_index: usize, data: &GameData) -> Vec<QuestDefinition> {
+    let mut rc = vec![];
+    for key in data.instances[player_index].state_values.keys() {
+        if key.starts_with("quest:") && is_quest_active(player_index, &key[6..], data) {
+            if let Some(quest) = get_quest(&key[6..], data) {
+                rc.push(quest);
+            }
+        }
+    }
+    rc
+}
+
+fn get_quest_stage(player_index: usize, quest: &QuestDefinition, data: &GameData) -> Option<QuestStage> {
+    if let Some(value) = data.instances[player_index].state_values.get(&get_quest_key(&quest.name)) {
+        return quest.stages.get(value.0 as usize).cloned();
+    }
+    None
+}
+
+fn get_required_progress(objective: &QuestObjective) -> usize {
+    match objective {
+        QuestObjective::None => 0,
+        QuestObjective::Kill(count, _) => *count,
+        _ => 1,
+    }
+}
+
+fn add_quest_progress(player_index: usize, quest: &QuestDefinition, data: &mut GameData) {
+    if let Some(value) = data.instances[player_index].state_values.get_mut(&get_quest_key(&quest.name)) {
+        value.1 += 1.0;
+    }
+}
+
+/// Advances the quest while the objective of the current stage is reached
+fn update_quest(player_index: usize, quest: &QuestDefinition, data: &mut GameData) {
+    while is_quest_active(player_index, &quest.name, data) {
+        let mut done = false;
+        if let Some(stage) = get_quest_stage(player_index, quest, data) {
+            let progress = data.instances[player_index].state_values[&get_quest_key(&quest.name)].1 as usize;
+            done = match &stage.objective {
+                QuestObjective::None => false,
+                QuestObjective::Area(name) => is_in_area(player_index, name, data),
+                QuestObjective::Item(name) => data.instances[player_index].inventory.iter().any(|item| item.name.eq_ignore_ascii_case(name)),
+                _ => progress >= get_required_progress(&stage.objective),
+            };
+        }
+        if done == false {
+            break;
+        }
+        next_quest_stage(player_index, quest, data);
+    }
+}
+
+fn next_quest_stage(player_index: usize, quest: &QuestDefinition, data: &mut GameData) {
+    let mut stage = 0;
+    if let Some(value) = data.instances[player_index].state_values.get_mut(&get_quest_key(&quest.name)) {
+        value.0 += 1.0;
+        value.1 = 0.0;
+        stage = value.0 as usize;
+    }
+    if stage >= quest.stages.len() {
+        set_quest_completed(player_index, quest, data);
+    } else {
+        send_status_message(player_index, format!("Quest updated: {}.", quest.name), data);
+    }
+}
+
+fn set_quest_completed(player_index: usize, quest: &QuestDefinition, data: &mut GameData) {
+    if let Some(value) = data.instances[player_index].state_values.get_mut(&get_quest_key(&quest.name)) {
+        value.2 = 1.0;
+    }
+    send_status_message(player_index, format!("Quest completed: {}.", quest.name), data);
+}
+
+fn is_in_area(player_index: usize, name: &str, data: &GameData) -> bool {
+    if let Some(p) = data.instances[player_index].position {
+        if let Some(region) = data.regions.get(&p.0) {
+            for area in &region.data.areas {
+                if area.name.eq_ignore_ascii_case(name) && area.area.contains(&(p.1, p.2)) {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stage_objectives() {
+        let stages = parse_quest_stages("// Objective, Description\ntalk Smith, Talk to the smith\narea Dark Forest, Enter the forest\nkill 3 Goblin, Kill the goblins\nitem Sword, Find the sword\nnone, Wait for the king");
+        assert_eq!(stages.len(), 5);
+        assert_eq!(stages[0].objective, QuestObjective::Talk("Smith".to_string()));
+        assert_eq!(stages[0].description, "Talk to the smith");
+        assert_eq!(stages[1].objective, QuestObjective::Area("Dark Forest".to_string()));
+        assert_eq!(stages[2].objective, QuestObjective::Kill(3, "Goblin".to_string()));
+        assert_eq!(stages[3].objective, QuestObjective::Item("Sword".to_string()));
+        assert_eq!(stages[4].objective, QuestObjective::None);
+    }
+
+    #[test]
+    fn parse_stage_edge_cases() {
+        let stages = parse_quest_stages("\n   \nKILL Goblin\nkill many Orc, Kill orcs\nwander around, Unknown objective");
+        assert_eq!(stages.len(), 3);
+        assert_eq!(stages[0].objective, QuestObjective::Kill(1, "Goblin".to_string()));
+        assert_eq!(stages[0].description, "");
+        assert_eq!(stages[1].objective, QuestObjective::Kill(1, "many Orc".to_string()));
+        assert_eq!(stages[2].objective, QuestObjective::None);
+        assert_eq!(stages[2].description, "Unknown objective");
+    }
+}
@@ -1,9 +1,15 @@
 use crate::gamedata::{ GameData, BehaviorNodeConnector, BehaviorType };
+use core_shared::clock::{ parse_time_of_day, MINUTES_PER_DAY };
 use core_shared::asset::TileUsage;
+use core_shared::actions::PlayerDirection;
+use core_shared::message::{MessageData, MessageType};
 
-use pathfinding::prelude::bfs;
+use pathfinding::prelude::{ astar, bfs, bfs_reach };
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
-use super::behavior::{BehaviorInstanceState};
+use super::behavior::{BehaviorInstanceState, BehaviorInstanceType, BehaviorNodeType, CachedPath};
+use super::script::{ eval_bool_expression_instance, eval_number_expression_instance };
 
 /// Retrieves a number instance value
 pub fn get_number_variable(instance_index: usize, variable: String, data: &mut GameData) -> Option<f64> {
@@ -64,6 +70,15 @@ pub fn get_node_value(id: (usize, usize, &str), data: &mut GameData, behavior_ty
             }
         }
     } else
+    if behavior_type == BehaviorType::Items {
+        if let Some(item) = data.items.get_mut(&id.0) {
+            if let Some(node) = item.data.nodes.get_mut(&id.1) {
+                if let Some(value) = node.values.get_mut(id.2) {
+                    return Some(value.clone());
+                }
+            }
+        }
+    } else
     if behavior_type == BehaviorType::GameLogic {
         let game = &mut data.game.behavior;
         if let Some(node) = game.data.nodes.get_mut(&id.1) {
@@ -95,6 +110,15 @@ pub fn set_node_value(id: (usize, usize, &str), data: &mut GameData, value: (f64
             }
         }
     } else
+    if behavior_type == BehaviorType::Items {
+        if let Some(item) = data.items.get_mut(&id.0) {
+            if let Some(node) = item.data.nodes.get_mut(&id.1) {
+                if let Some(v) = node.values.get_mut(id.2) {
+                    *v = value;
+                }
+            }
+        }
+    } else
     if behavior_type == BehaviorType::GameLogic {
         let game = &mut data.game.behavior;
         if let Some(node) = game.data.nodes.get_mut(&id.1) {
@@ -114,80 +138,841 @@ pub fn compute_distance(p0: &(usize, isize, isize), p1: &(usize, isize, isize))
 
 pub fn walk_towards(instance_index: usize, p: Option<(usize, isize, isize)>, dp: Option<(usize, isize, isize)>, exclude_dp: bool, data: &mut GameData) -> BehaviorNodeConnector {
 
-    // Cache the character positions
-    let mut char_positions : Vec<(usize, isize, isize)> = vec![];
+    let target = dp;
 
     if let Some(p) = p {
-        for inst_index in 0..data.instances.len() {
-            if inst_index != instance_index {
-                // Only track if the state is normal
-                if data.instances[inst_index].state == BehaviorInstanceState::Normal {
-                    if let Some(pos) = data.instances[inst_index].position {
-                        if p.0 == pos.0 {
-                            if exclude_dp == false {
-                                char_positions.push(pos);
-                            } else {
-                                // Exclude dp, otherwise the Close In tracking function does not find a route
-                                if let Some(dp) = dp {
-                                    if dp != pos {
-                                        char_positions.push(pos);
+        if let Some(mut dp) = dp {
+
+            // The destination is in another region, walk to the teleport area leading there
+            if p.0 != dp.0 {
+                if let Some(portal) = get_portal_position(p, dp.0, data.instances[instance_index].instance_type, data) {
+                    if portal == p {
+                        // The area behaviors run after the characters, the teleport did not fire for us
+                        return BehaviorNodeConnector::Fail;
+                    }
+                    dp = portal;
+                } else {
+                    return BehaviorNodeConnector::Fail;
+                }
+            }
+
+            if p.1 == dp.1 && p.2 == dp.2 {
+                data.paths.remove(&instance_index);
+                return BehaviorNodeConnector::Success;
+            }
+
+            // Other characters block the way, if exclude_dp is set the character at the destination does not,
+            // otherwise the Close In tracking function does not find a route
+            let is_blocked = |x: isize, y: isize| -> bool {
+                if let Some(region) = data.regions.get(&p.0) {
+                    for index in region.grid.at((x, y)) {
+                        if index != instance_index && data.instances[index].state == BehaviorInstanceState::Normal {
+                            if exclude_dp == false || target != Some((p.0, x, y)) {
+                                return true;
+                            }
+                        }
+                    }
+                }
+                false
+            };
+
+            // Reuse the cached path if nothing changed on its remaining steps since it was calculated
+            let mut next : Option<(isize, isize)> = None;
+            if let Some(path) = data.paths.get(&instance_index) {
+                if path.region == p.0 && path.destination == (dp.1, dp.2) && path.steps.len() > 1 && path.steps[0] == (p.1, p.2) && is_blocked(path.steps[1].0, path.steps[1].1) == false {
+                    if path.signature == get_path_signature(p.0, &path.steps, &is_blocked, data) {
+                        next = Some(path.steps[1]);
+                    }
+                }
+            }
+
+            if next.is_none() {
+                let diagonal = get_diagonal_movement(p.0, data);
+
+                let can_go = |x: isize, y: isize| -> Option<usize> {
+                    if is_blocked(x, y) {
+                        return None;
+                    }
+                    get_movement_cost((p.0, x, y), data)
+                };
+
+                let result = astar(&(p.1, p.2),
+                                    |&(x, y)| {
+                                    let mut v : Vec<((isize, isize), usize)> = vec![];
+                                    let straight = [(x + 1, y), (x, y + 1), (x - 1, y), (x, y - 1)];
+                                    for n in &straight {
+                                        if let Some(cost) = can_go(n.0, n.1) { v.push((*n, cost * 10)); }
                                     }
+                                    if diagonal {
+                                        for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                                            // Do not cut corners
+                                            if can_go(x + dx, y).is_some() && can_go(x, y + dy).is_some() {
+                                                if let Some(cost) = can_go(x + dx, y + dy) { v.push(((x + dx, y + dy), cost * 14)); }
+                                            }
+                                        }
+                                    }
+                                    v
+                                    },
+                                    |&(x, y)| get_path_heuristic((x, y), (dp.1, dp.2), diagonal),
+                                    |&p| p.0 == dp.1 && p.1 == dp.2);
+
+                data.paths.remove(&instance_index);
+                if let Some((steps, _cost)) = result {
+                    if steps.len() > 1 {
+                        next = Some(steps[1]);
+                        let signature = get_path_signature(p.0, &steps, &is_blocked, data);
+                        data.paths.insert(instance_index, CachedPath { region: p.0, destination: (dp.1, dp.2), signature, steps });
+                    }
+                }
+            }
+
+            if let Some(next) = next {
+                data.instances[instance_index].facing = get_direction((p.1, p.2), next);
+                data.instances[instance_index].position = Some((p.0, next.0, next.1));
+                data.update_instance_position(instance_index, Some(p));
+
+                if let Some(path) = data.paths.get_mut(&instance_index) {
+                    path.steps.remove(0);
+                }
+                return BehaviorNodeConnector::Right;
+            }
+        }
+    }
+
+    BehaviorNodeConnector::Fail
+}
+
+/// Reads the "speed" value of the node, 0 to 10, and lets the character sleep the remaining ticks before its next step
+pub fn apply_speed_delay(instance_index: usize, id: (usize, usize), data: &mut GameData, behavior_type: BehaviorType) {
+    let mut speed : f64 = 8.0;
+    if let Some(rc) = eval_number_expression_instance(instance_index, (behavior_type, id.0, id.1, "speed".to_string()), data) {
+        speed = rc;
+    }
+
+    let delay = 10.0 - speed.clamp(0.0, 10.0);
+    data.instances[instance_index].sleep_cycles = delay as usize;
+}
+
+/// True if the condition of the dialog choice is empty or evaluates to true in the scope of the character
+pub fn is_dialog_choice_available(instance_index: usize, id: (usize, usize), choice: usize, data: &mut GameData, behavior_type: BehaviorType) -> bool {
+    let key = format!("condition{}", choice);
+    if let Some(value) = get_node_value((id.0, id.1, key.as_str()), data, behavior_type, 0) {
+        if value.4.trim().is_empty() == false {
+            return eval_bool_expression_instance(instance_index, (behavior_type, id.0, id.1, key), data) == Some(true);
+        }
+    }
+    true
+}
+
+/// The tiles the character at p can walk to without leaving the given distance around p, other characters block the way
+pub fn get_reachable_tiles(instance_index: usize, p: (usize, isize, isize), max_distance: f64, data: &GameData) -> Vec<(usize, isize, isize)> {
+    let is_free = |x: isize, y: isize| -> bool {
+        if compute_distance(&p, &(p.0, x, y)) > max_distance || get_movement_cost((p.0, x, y), data).is_none() {
+            return false;
+        }
+        if let Some(region) = data.regions.get(&p.0) {
+            for index in region.grid.at((x, y)) {
+                if index != instance_index && data.instances[index].state == BehaviorInstanceState::Normal {
+                    return false;
+                }
+            }
+        }
+        true
+    };
+
+    bfs_reach((p.1, p.2), |&(x, y)| {
+        [(x + 1, y), (x, y + 1), (x - 1, y), (x, y - 1)].into_iter().filter(|n| is_free(n.0, n.1)).collect::<Vec<(isize, isize)>>()
+    }).map(|(x, y)| (p.0, x, y)).collect()
+}
+
+/// Returns the tile of the teleport area a character at p has to walk to in order to reach the given region.
+/// The regions are routed through the region graph, the closest walkable tile of the first teleport area is returned.
+/// Only teleport areas whose trigger fires for the given instance type are used.
+pub fn get_portal_position(p: (usize, isize, isize), region_id: usize, instance_type: BehaviorInstanceType, data: &GameData) -> Option<(usize, isize, isize)> {
+
+    let route = bfs(&p.0,
+                    |id| {
+                        let mut v : Vec<usize> = vec![];
+                        if let Some(portals) = data.portals.get(id) {
+                            for portal in portals {
+                                if portal.instance_types.contains(&instance_type) && data.regions.contains_key(&portal.destination.0) && v.contains(&portal.destination.0) == false {
+                                    v.push(portal.destination.0);
                                 }
                             }
                         }
+                        v
+                    },
+                    |id| *id == region_id);
+
+    if let Some(route) = route {
+        if route.len() > 1 {
+            let mut best : Option<(usize, isize, isize)> = None;
+            let mut best_distance = isize::MAX;
+
+            if let Some(portals) = data.portals.get(&p.0) {
+                for portal in portals {
+                    if portal.destination.0 == route[1] && portal.instance_types.contains(&instance_type) {
+                        for (x, y) in &portal.area {
+                            let distance = (x - p.1).abs() + (y - p.2).abs();
+                            if distance < best_distance && get_movement_cost((p.0, *x, *y), data).is_some() {
+                                best = Some((p.0, *x, *y));
+                                best_distance = distance;
+                            }
+                        }
                     }
                 }
             }
+            return best;
         }
     }
+    None
+}
 
-    if let Some(p) = p {
+/// Returns the cost of walking onto the given tile or None if the tile cannot be entered. Roads are cheaper
+/// than other terrain, tiles with a movement cost override the default of their usage.
+pub fn get_movement_cost(pos: (usize, isize, isize), data: &GameData) -> Option<usize> {
+    let tiles = data.get_tile_at(pos);
+    if tiles.is_empty() { return None; }
 
-        let can_go = |x: isize, y: isize| -> bool {
+    let mut cost = 2;
+    for tile in tiles {
+        if tile.3 == TileUsage::EnvBlocking || tile.3 == TileUsage::Water {
+            return None;
+        }
 
-            // Check tiles
-            let tiles = data.get_tile_at((p.0, x, y));
-            if tiles.is_empty() { return false; }
-            for tile in tiles {
-                if tile.3 == TileUsage::EnvBlocking || tile.3 == TileUsage::Water {
-                    return false;
+        // The upper layers decide the cost, i.e. a road drawn on grass
+        if tile.3 == TileUsage::EnvRoad {
+            cost = 1;
+        } else
+        if tile.3 == TileUsage::Environment {
+            cost = 2;
+        }
+
+        if let Some(asset) = &data.asset {
+            if let Some(map) = asset.tileset.maps.get(&tile.0) {
+                if let Some(t) = map.settings.tiles.get(&(tile.1, tile.2)) {
+                    if t.movement_cost > 0 {
+                        cost = t.movement_cost;
+                    }
                 }
             }
+        }
+    }
+    Some(cost)
+}
 
-            // Check characters
-            for char_p in &char_positions {
-                if char_p.1 == x && char_p.2 == y {
-                    return false;
+/// Returns true if characters in the given region can move diagonally
+pub fn get_diagonal_movement(region_id: usize, data: &GameData) -> bool {
+    if let Some(region) = data.regions.get(&region_id) {
+        if let Some(property) = region.data.settings.get("diagonal_movement") {
+            if let Some(value) = property.as_bool() {
+                return value;
+            }
+        }
+    }
+    false
+}
+
+/// The A* heuristic, the distance assuming the cheapest possible tile cost
+fn get_path_heuristic(from: (isize, isize), to: (isize, isize), diagonal: bool) -> usize {
+    let dx = (to.0 - from.0).abs() as usize;
+    let dy = (to.1 - from.1).abs() as usize;
+    if diagonal {
+        10 * dx.max(dy) + 4 * dx.min(dy)
+    } else {
+        10 * (dx + dy)
+    }
+}
+
+/// Hashes the tile displacements of a region and the blocked tiles on the remaining steps of a path (the first step is the
+/// position of the walker), a cached path is only valid while this stays the same
+fn get_path_signature<F: Fn(isize, isize) -> bool>(region_id: usize, steps: &[(isize, isize)], is_blocked: &F, data: &GameData) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    for (x, y) in steps.iter().skip(1) {
+        if is_blocked(*x, *y) {
+            (x, y).hash(&mut hasher);
+        }
+    }
+
+    if let Some(region) = data.regions.get(&region_id) {
+        // Displacements are stored in a HashMap, combine them independent of their order
+        let mut displacements : u64 = 0;
+        for (pos, tile) in &region.displacements {
+            let mut h = DefaultHasher::new();
+            pos.hash(&mut h);
+            (tile.0, tile.1, tile.2).hash(&mut h);
+            displacements = displacements.wrapping_add(h.finish());
+        }
+        displacements.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// The direction of a step between two adjacent tiles
+pub fn get_direction(from: (isize, isize), to: (isize, isize)) -> PlayerDirection {
+    if to.1 < from.1 { PlayerDirection::North } else
+    if to.1 > from.1 { PlayerDirection::South } else
+    if to.0 > from.0 { PlayerDirection::East } else
+    if to.0 < from.0 { PlayerDirection::West } else { PlayerDirection::None }
+}
+
+/// Returns true if nothing blocks the line between the two tiles. The tiles on the line are traced with Bresenham,
+/// the start and end tiles themselves never block. A diagonal step between two blocking tiles is blocked.
+pub fn line_of_sight<F: Fn(isize, isize) -> bool>(from: (isize, isize), to: (isize, isize), is_blocking: F) -> bool {
+    let dx = (to.0 - from.0).abs();
+    let dy = -(to.1 - from.1).abs();
+    let sx = if from.0 < to.0 { 1 } else { -1 };
+    let sy = if from.1 < to.1 { 1 } else { -1 };
+
+    let mut err = dx + dy;
+    let (mut x, mut y) = from;
+
+    while (x, y) != to {
+        let (px, py) = (x, y);
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        if px != x && py != y && is_blocking(x, py) && is_blocking(px, y) {
+            return false;
+        }
+        if (x, y) != to && is_blocking(x, y) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns true if the tile lies inside the view cone of the given angle (in degrees) around the facing direction.
+/// Without a facing direction or with an angle of 360 degrees everything is inside.
+pub fn in_view_cone(from: (isize, isize), facing: PlayerDirection, angle: f64, to: (isize, isize)) -> bool {
+    if angle >= 360.0 || from == to {
+        return true;
+    }
+
+    let dir : (f64, f64) = match facing {
+        PlayerDirection::North => (0.0, -1.0),
+        PlayerDirection::South => (0.0, 1.0),
+        PlayerDirection::East => (1.0, 0.0),
+        PlayerDirection::West => (-1.0, 0.0),
+        _ => return true,
+    };
+
+    let dx = (to.0 - from.0) as f64;
+    let dy = (to.1 - from.1) as f64;
+    let cos = (dx * dir.0 + dy * dir.1) / (dx * dx + dy * dy).sqrt();
+
+    // Tiles exactly on the edge of the cone are inside
+    cos + 1e-9 >= (angle.max(0.0) / 2.0).to_radians().cos()
+}
+
+/// Returns true if the tile at position to can be seen from position from. Tiles with TileUsage::EnvBlocking occlude the view.
+pub fn is_visible(from: (usize, isize, isize), to: (usize, isize, isize), data: &GameData) -> bool {
+    if from.0 != to.0 {
+        return false;
+    }
+
+    if let Some(region) = data.regions.get(&from.0) {
+        return line_of_sight((from.1, from.2), (to.1, to.2), |x, y| {
+            region.get_value((x, y)).iter().any(|tile| tile.3 == TileUsage::EnvBlocking)
+        });
+    }
+    false
+}
+
+/// The field of view of the instance: all tiles within the given distance which are visible and inside its view cone
+pub fn compute_fov(instance_index: usize, max_distance: f64, angle: f64, data: &GameData) -> Vec<(isize, isize)> {
+    if let Some(p) = data.instances[instance_index].position {
+        if let Some(region) = data.regions.get(&p.0) {
+            return get_fov_tiles((p.1, p.2), data.instances[instance_index].facing, max_distance, angle, |x, y| {
+                region.get_value((x, y)).iter().any(|tile| tile.3 == TileUsage::EnvBlocking)
+            });
+        }
+    }
+    vec![]
+}
+
+/// All tiles within the given distance of the position which are inside the view cone and in line of sight
+pub fn get_fov_tiles<F: Fn(isize, isize) -> bool>(p: (isize, isize), facing: PlayerDirection, max_distance: f64, angle: f64, is_blocking: F) -> Vec<(isize, isize)> {
+    let mut tiles = vec![];
+
+    let r = max_distance.max(0.0) as isize;
+    for y in p.1 - r..=p.1 + r {
+        for x in p.0 - r..=p.0 + r {
+            let dx = (x - p.0) as f64;
+            let dy = (y - p.1) as f64;
+            if (dx * dx + dy * dy).sqrt() <= max_distance && in_view_cone(p, facing, angle, (x, y)) && line_of_sight(p, (x, y), &is_blocking) {
+                tiles.push((x, y));
+            }
+        }
+    }
+    tiles
+}
+
+/// Returns true if the instance can see the other instance
+pub fn can_see(instance_index: usize, other_index: usize, max_distance: f64, angle: f64, data: &GameData) -> bool {
+    if let Some(p) = data.instances[instance_index].position {
+        if let Some(o) = data.instances[other_index].position {
+            return p.0 == o.0 && compute_distance(&p, &o) <= max_distance && in_view_cone((p.1, p.2), data.instances[instance_index].facing, angle, (o.1, o.2)) && is_visible(p, o, data);
+        }
+    }
+    false
+}
+
+/// The position in the direction of the current player action, or the position of the player if the action has no direction
+pub fn get_action_position(instance_index: usize, data: &GameData) -> Option<(usize, isize, isize)> {
+    if let Some(p) = data.instances[instance_index].position {
+        if let Some(action) = &data.instances[instance_index].action {
+            return match action.direction {
+                PlayerDirection::North => Some((p.0, p.1, p.2 - 1)),
+                PlayerDirection::South => Some((p.0, p.1, p.2 + 1)),
+                PlayerDirection::East => Some((p.0, p.1 + 1, p.2)),
+                PlayerDirection::West => Some((p.0, p.1 - 1, p.2)),
+                _ => Some(p),
+            };
+        }
+        return Some(p);
+    }
+    None
+}
+
+/// The inventory index of the item named in the text of the current player action
+pub fn get_action_inventory_index(instance_index: usize, data: &GameData) -> Option<usize> {
+    if let Some(action) = &data.instances[instance_index].action {
+        return data.instances[instance_index].inventory.iter().position(|item| item.name.eq_ignore_ascii_case(&action.text));
+    }
+    None
+}
+
+/// The character the player wants to talk to, the character in the action direction or, if the action has no direction, the first character next to the player
+pub fn get_talk_partner(instance_index: usize, data: &GameData) -> Option<usize> {
+    let mut direction = PlayerDirection::None;
+    if let Some(action) = &data.instances[instance_index].action {
+        direction = action.direction;
+    }
+
+    for index in 0..data.instances.len() {
+        if index == instance_index || data.instances[index].instance_type != BehaviorInstanceType::NonPlayerCharacter || data.instances[index].state != BehaviorInstanceState::Normal {
+            continue;
+        }
+        if direction == PlayerDirection::None {
+            if is_adjacent(instance_index, index, data) {
+                return Some(index);
+            }
+        } else
+        if data.instances[index].position.is_some() && data.instances[index].position == get_action_position(instance_index, data) {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// True if both instances are in the same region and next to each other
+pub fn is_adjacent(instance_index: usize, other_index: usize, data: &GameData) -> bool {
+    if let Some(p) = data.instances[instance_index].position {
+        if let Some(o) = data.instances[other_index].position {
+            return p.0 == o.0 && compute_distance(&p, &o) < 1.5;
+        }
+    }
+    false
+}
+
+/// Replaces the ${} expressions in the text, Self and Target are set to the names of the instance and its target
+pub fn interpolate_text(instance_index: usize, text: String, data: &mut GameData) -> String {
+    if text.contains("${") {
+        data.scopes[instance_index].push("Self", data.instances[instance_index].name.clone());
+        if let Some(target_index) = data.instances[instance_index].target_instance_index {
+            data.scopes[instance_index].push("Target", data.instances[target_index].name.clone());
+        }
+        let r = data.engine.eval_with_scope::<String>(&mut data.scopes[instance_index], format!("`{}`", text).as_str());
+        if let Some(rc) = r.ok() {
+            return rc;
+        }
+    }
+    text
+}
+
+/// The faction of the instance, the "faction" variable of its scope. Empty if it has none.
+pub fn get_faction(instance_index: usize, data: &GameData) -> String {
+    if let Some(scope) = data.scopes.get(instance_index) {
+        if let Some(faction) = scope.get_value::<String>("faction") {
+            return faction;
+        }
+    }
+    "".to_string()
+}
+
+/// Checks the faction filter of Lookout and Close In, 0 accepts everyone, 1 only hostile and 2 only non hostile characters
+pub fn matches_faction_filter(instance_index: usize, other_index: usize, filter: usize, data: &GameData) -> bool {
+    if filter == 0 {
+        return true;
+    }
+    let hostile = data.factions.is_hostile(&get_faction(instance_index, data), &get_faction(other_index, data));
+    if filter == 1 { hostile } else { hostile == false }
+}
+
+/// The instance index of the instance with the given id
+pub fn get_instance_index_of_id(id: usize, data: &GameData) -> Option<usize> {
+    data.instances.iter().position(|instance| instance.id == id)
+}
+
+/// The leader of the party of the instance, the leader holds the ids of all members (including itself) in its party
+pub fn get_party_leader(instance_index: usize, data: &GameData) -> Option<usize> {
+    if data.instances[instance_index].party.is_empty() == false {
+        return Some(instance_index);
+    }
+    let id = data.instances[instance_index].id;
+    data.instances.iter().position(|instance| instance.party.contains(&id))
+}
+
+/// The instance indices of all living members of the party of the instance, including the leader
+pub fn get_party_members(instance_index: usize, data: &GameData) -> Vec<usize> {
+    let mut members = vec![];
+    if let Some(leader) = get_party_leader(instance_index, data) {
+        for id in &data.instances[leader].party {
+            if let Some(index) = get_instance_index_of_id(*id, data) {
+                if data.instances[index].state != BehaviorInstanceState::Killed && data.instances[index].state != BehaviorInstanceState::Purged {
+                    members.push(index);
                 }
             }
+        }
+    }
+    members
+}
+
+/// Sends a status message to the instance and the editor log
+pub fn send_status_message(instance_index: usize, text: String, data: &mut GameData) {
+    let message_data = MessageData { message_type: MessageType::Status, message: text.clone(), from: "System".to_string() };
+    data.instances[instance_index].messages.push(message_data);
+    data.messages.push((text, MessageType::Status));
+}
 
-            true
-        };
+/// An entry of a Schedule node, "08:00 - 18:00, Shop, Open Shop"
+#[derive(Clone, Debug)]
+pub struct ScheduleEntry {
+    pub from                    : usize,
+    pub to                      : usize,
 
-        if let Some(dp) = dp {
+    /// The name of an area or an "x y" position in the region of the character
+    pub destination             : String,
 
-            let result = bfs(&(p.1, p.2),
-                                |&(x, y)| {
-                                let mut v : Vec<(isize, isize)> = vec![];
-                                if can_go(x + 1, y) { v.push((x + 1, y))};
-                                if can_go(x, y + 1) { v.push((x, y + 1))};
-                                if can_go(x - 1, y) { v.push((x - 1, y))};
-                                if can_go(x, y - 1) { v.push((x, y - 1))};
-                                v
-                                },
-                                |&p| p.0 == dp.1 && p.1 == dp.2);
+    /// The behavior tree to execute once the destination is reached, may be empty
+    pub tree                    : String,
+}
+
+/// Parses the text of a Schedule node, one entry per line, lines starting with // are ignored
+pub fn parse_schedule(text: &str) -> Vec<ScheduleEntry> {
+    let mut rc = vec![];
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        let mut parts = line.splitn(3, ',');
+        let range = parts.next().unwrap_or("");
+        let destination = parts.next().unwrap_or("").trim().to_string();
+        let tree = parts.next().unwrap_or("").trim().to_string();
 
-            if let Some(result) = result {
-                if result.len() > 1 {
-                    data.instances[instance_index].position = Some((p.0, result[1].0, result[1].1));
-                    return BehaviorNodeConnector::Right;
-                } else
-                if result.len() == 1 && dp.1 == result[0].0 && dp.2 == result[0].1 {
-                    return BehaviorNodeConnector::Success;
+        let mut times = range.split('-');
+        if let Some(from) = times.next().and_then(parse_time_of_day) {
+            if let Some(to) = times.next().and_then(parse_time_of_day) {
+                if destination.is_empty() == false {
+                    rc.push(ScheduleEntry { from, to, destination, tree });
                 }
             }
         }
     }
+    rc
+}
 
-    BehaviorNodeConnector::Fail
-}
\ No newline at end of file
+/// The time of day of a schedule whose day lasts the given game hours and is shifted by the offset in game hours,
+/// scaled to minutes since midnight so the entries of every schedule use the "HH:MM" times of a 24 hour day
+pub fn get_schedule_minutes(total_minutes: usize, day_length: f64, offset: f64) -> usize {
+    let day_minutes = ((day_length * 60.0).round() as isize).max(1);
+    let minutes = (total_minutes as isize + (offset * 60.0).round() as isize).rem_euclid(day_minutes);
+    (minutes * MINUTES_PER_DAY as isize / day_minutes) as usize
+}
+
+/// Returns the walkable tiles the destination of a schedule entry refers to. The destination is either
+/// an "x y" position in the given region or the name of an area, the given region is searched first.
+pub fn get_schedule_destination(destination: &str, region_id: usize, data: &GameData) -> Vec<(usize, isize, isize)> {
+    let coords : Vec<&str> = destination.split_whitespace().collect();
+    if coords.len() == 2 {
+        if let Some(x) = coords[0].parse::<isize>().ok() {
+            if let Some(y) = coords[1].parse::<isize>().ok() {
+                return vec![(region_id, x, y)];
+            }
+        }
+    }
+
+    let mut region_ids : Vec<usize> = data.regions.keys().cloned().filter(|id| *id != region_id).collect();
+    region_ids.sort();
+    region_ids.insert(0, region_id);
+
+    for id in region_ids {
+        if let Some(region) = data.regions.get(&id) {
+            for area in &region.data.areas {
+                if area.name.eq_ignore_ascii_case(destination) {
+                    return area.area.iter().map(|p| (id, p.0, p.1)).filter(|p| get_movement_cost(*p, data).is_some()).collect();
+                }
+            }
+        }
+    }
+    vec![]
+}
+
+/// Returns the id of the behavior tree the given node belongs to
+pub fn get_behavior_tree_of_node(behavior_id: usize, node_id: usize, data: &GameData) -> Option<usize> {
+    if let Some(behavior) = data.behaviors.get(&behavior_id) {
+        let mut current = node_id;
+        let mut visited = vec![];
+        while visited.contains(&current) == false {
+            if let Some(node) = behavior.data.nodes.get(&current) {
+                if node.behavior_type == BehaviorNodeType::BehaviorTree {
+                    return Some(current);
+                }
+            }
+            visited.push(current);
+            if let Some(c) = behavior.data.connections.iter().find(|c| c.2 == current) {
+                current = c.0;
+            } else {
+                break;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::region::GameRegion;
+
+    #[test]
+    fn parse_schedule_entries() {
+        let entries = parse_schedule("// From - To, Area or X Y, Tree\n08:00 - 18:00, Shop, Open Shop\n\n22 - 6, 10 12\n25:00 - 26:00, Nowhere\n09:00 - 10:00");
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].from, entries[0].to), (8 * 60, 18 * 60));
+        assert_eq!(entries[0].destination, "Shop");
+        assert_eq!(entries[0].tree, "Open Shop");
+        assert_eq!((entries[1].from, entries[1].to), (22 * 60, 6 * 60));
+        assert_eq!(entries[1].destination, "10 12");
+        assert_eq!(entries[1].tree, "");
+    }
+
+    #[test]
+    fn schedule_minutes_of_the_game_day() {
+        assert_eq!(get_schedule_minutes(8 * 60, 24.0, 0.0), 8 * 60);
+        assert_eq!(get_schedule_minutes(MINUTES_PER_DAY + 90, 24.0, 0.0), 90);
+        assert_eq!(get_schedule_minutes(8 * 60, 24.0, 2.0), 10 * 60);
+        assert_eq!(get_schedule_minutes(60, 24.0, -2.0), 23 * 60);
+    }
+
+    #[test]
+    fn schedule_minutes_of_a_shorter_day() {
+        // A day of 12 game hours runs twice as fast as the game clock
+        assert_eq!(get_schedule_minutes(3 * 60, 12.0, 0.0), 6 * 60);
+        assert_eq!(get_schedule_minutes(12 * 60, 12.0, 0.0), 0);
+        assert_eq!(get_schedule_minutes(15 * 60, 12.0, 0.0), 6 * 60);
+        assert_eq!(get_schedule_minutes(60, 0.0, 0.0), 0);
+    }
+
+    /// The walls ('#') of a hand drawn grid, the top left character is at (0, 0)
+    fn walls(rows: &[&str]) -> Vec<(isize, isize)> {
+        let mut walls = vec![];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    walls.push((x as isize, y as isize));
+                }
+            }
+        }
+        walls
+    }
+
+    #[test]
+    fn line_of_sight_blocked() {
+        let walls = walls(&[
+            ".....",
+            "..#..",
+            ".....",
+        ]);
+        let is_blocking = |x, y| walls.contains(&(x, y));
+
+        assert!(line_of_sight((0, 1), (4, 1), is_blocking) == false);
+        assert!(line_of_sight((4, 1), (0, 1), is_blocking) == false);
+        assert!(line_of_sight((2, 0), (2, 2), is_blocking) == false);
+        assert!(line_of_sight((0, 0), (4, 0), is_blocking));
+        assert!(line_of_sight((0, 2), (4, 2), is_blocking));
+    }
+
+    #[test]
+    fn line_of_sight_diagonal_past_a_corner() {
+        let walls = walls(&[
+            ".#...",
+            "#....",
+            ".....",
+        ]);
+        let is_blocking = |x, y| walls.contains(&(x, y));
+
+        // The exact diagonal can not squeeze between the two walls touching at the corner
+        assert!(line_of_sight((0, 0), (2, 2), is_blocking) == false);
+        assert!(line_of_sight((2, 2), (0, 0), is_blocking) == false);
+        assert!(line_of_sight((0, 0), (1, 1), is_blocking) == false);
+
+        // A single wall beside the diagonal does not block it
+        assert!(line_of_sight((1, 1), (3, 3), is_blocking));
+        assert!(line_of_sight((0, 2), (2, 0), |x, y| (x, y) == (0, 1)));
+
+        // A flatter line runs into the wall beside the start
+        assert!(line_of_sight((0, 0), (3, 1), is_blocking) == false);
+    }
+
+    #[test]
+    fn line_of_sight_ignores_start_and_end() {
+        let walls = walls(&[
+            "#...#",
+        ]);
+        let is_blocking = |x, y| walls.contains(&(x, y));
+
+        assert!(line_of_sight((0, 0), (4, 0), is_blocking));
+        assert!(line_of_sight((0, 0), (0, 0), is_blocking));
+        assert!(line_of_sight((0, 0), (1, 0), is_blocking));
+    }
+
+    #[test]
+    fn view_cone_edges() {
+        let cases = [
+            (PlayerDirection::North, (0, -2), [(-2, -2), (2, -2)], [(-2, -1), (2, -1), (0, 2)]),
+            (PlayerDirection::South, (0, 2), [(-2, 2), (2, 2)], [(-2, 1), (2, 1), (0, -2)]),
+            (PlayerDirection::East, (2, 0), [(2, -2), (2, 2)], [(1, -2), (1, 2), (-2, 0)]),
+            (PlayerDirection::West, (-2, 0), [(-2, -2), (-2, 2)], [(-1, -2), (-1, 2), (2, 0)]),
+        ];
+
+        for (facing, ahead, edges, outside) in cases {
+            assert!(in_view_cone((0, 0), facing, 90.0, ahead), "{:?} ahead", facing);
+            for edge in edges {
+                assert!(in_view_cone((0, 0), facing, 90.0, edge), "{:?} edge {:?}", facing, edge);
+            }
+            for tile in outside {
+                assert!(in_view_cone((0, 0), facing, 90.0, tile) == false, "{:?} outside {:?}", facing, tile);
+            }
+            assert!(in_view_cone((0, 0), facing, 0.0, ahead), "{:?} zero angle", facing);
+            assert!(in_view_cone((0, 0), facing, 360.0, outside[2]), "{:?} full circle", facing);
+        }
+
+        // Without a facing direction everything is inside
+        assert!(in_view_cone((0, 0), PlayerDirection::None, 90.0, (0, 5)));
+    }
+
+    #[test]
+    fn fov_through_a_doorway() {
+        let walls = walls(&[
+            ".......",
+            ".......",
+            "###.###",
+            ".......",
+            ".......",
+        ]);
+        let is_blocking = |x, y| walls.contains(&(x, y));
+
+        let fov = get_fov_tiles((3, 4), PlayerDirection::North, 4.0, 360.0, is_blocking);
+
+        // Straight through the door and the walls themselves are visible
+        assert!(fov.contains(&(3, 2)));
+        assert!(fov.contains(&(3, 0)));
+        assert!(fov.contains(&(2, 2)) && fov.contains(&(4, 2)));
+        assert!(fov.contains(&(3, 1)));
+
+        // The sides of the room behind the wall are hidden
+        assert!(fov.contains(&(0, 1)) == false);
+        assert!(fov.contains(&(6, 1)) == false);
+        assert!(fov.contains(&(1, 0)) == false);
+
+        // The cone limits the view to the north
+        let fov = get_fov_tiles((3, 4), PlayerDirection::North, 4.0, 90.0, is_blocking);
+        assert!(fov.contains(&(3, 0)));
+        assert!(fov.contains(&(0, 4)) == false);
+    }
+
+    /// A region built from hand drawn rows, '#' are blocking walls and all other characters floor tiles
+    fn region_from_rows(id: usize, rows: &[&str]) -> GameRegion {
+        let mut region = GameRegion::new(&std::path::PathBuf::from("test"), &std::path::PathBuf::new());
+        region.data.id = id;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let usage = if c == '#' { TileUsage::EnvBlocking } else { TileUsage::Environment };
+                region.data.layer1.insert((x as isize, y as isize), (0, 0, 0, usage));
+            }
+        }
+        region
+    }
+
+    #[test]
+    fn visible_in_a_region() {
+        let mut data = GameData::new();
+        data.regions.insert(1, region_from_rows(1, &[
+            ".....#.",
+            "..#.#..",
+            ".......",
+        ]));
+
+        assert!(is_visible((1, 0, 0), (1, 4, 0), &data));
+        assert!(is_visible((1, 0, 1), (1, 4, 1), &data) == false);
+        assert!(is_visible((1, 3, 0), (1, 3, 2), &data));
+
+        // The walls at (5, 0) and (4, 1) touch at the corner
+        assert!(is_visible((1, 4, 0), (1, 5, 1), &data) == false);
+
+        // Other or unknown regions are never visible
+        assert!(is_visible((1, 0, 0), (2, 0, 0), &data) == false);
+        assert!(is_visible((2, 0, 0), (2, 1, 0), &data) == false);
+    }
+
+    /// The game of the repository, the player starts at (542323, 22, 4). With embedded binaries the game is
+    /// loaded from the embedded files instead, the tests using it only run without.
+    #[cfg(not(feature = "embed_binaries"))]
+    fn load_game() -> GameData<'static> {
+        let mut data = GameData::load_from_path(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."));
+        data.startup();
+        data
+    }
+
+    #[test]
+    #[cfg(not(feature = "embed_binaries"))]
+    fn cached_path_is_reused_across_ticks() {
+        let mut data = load_game();
+        let inst = data.player_ids_inst_indices[&131313];
+        let region = 542323;
+
+        // A detour A* would not pick, it is only followed if the cached path is reused
+        let steps = vec![(22, 4), (21, 4), (21, 3), (21, 2), (22, 2), (23, 2), (24, 2)];
+        let signature = get_path_signature(region, &steps, &|_, _| false, &data);
+        data.paths.insert(inst, CachedPath { region, destination: (24, 2), signature, steps });
+
+        for expected in [(21, 4), (21, 3)] {
+            data.tick();
+            let p = data.instances[inst].position;
+            assert_eq!(walk_towards(inst, p, Some((region, 24, 2)), false, &mut data), BehaviorNodeConnector::Right);
+            assert_eq!(data.instances[inst].position, Some((region, expected.0, expected.1)));
+        }
+
+        // A character blocking a remaining step invalidates the path
+        data.tick();
+        let orc = data.instances.iter().position(|instance| instance.name == "Orc").unwrap();
+        let old_position = data.instances[orc].position;
+        data.instances[orc].position = Some((region, 22, 2));
+        data.update_instance_position(orc, old_position);
+
+        let p = data.instances[inst].position;
+        assert_eq!(walk_towards(inst, p, Some((region, 24, 2)), false, &mut data), BehaviorNodeConnector::Right);
+        assert_eq!(data.instances[inst].position, Some((region, 22, 3)));
+        assert!(data.paths[&inst].steps.contains(&(22, 2)) == false);
+    }
+}
@@ -12,11 +12,23 @@ use core_shared::regiondata::{ GameRegionData, RegionArea };
 use core_shared::asset::tileset::TileUsage;
 use core_shared::asset::Asset;
 
-use super::behavior::GameBehavior;
+use super::behavior::{ GameBehavior, BehaviorNodeType, BehaviorInstanceType, default_data_id };
+use super::spatial::SpatialGrid;
+use super::nodes_area::area_trigger_applies;
 
 #[cfg(feature = "embed_binaries")]
 use core_embed_binaries::Embedded;
 
+/// A teleport area of a region, characters route through it to reach other regions
+#[derive(Clone, Debug)]
+pub struct RegionPortal {
+    pub area            : Vec<(isize, isize)>,
+    pub destination     : (usize, isize, isize),
+
+    /// The instance types an Enter Area or Inside Area trigger leading to the teleport fires for
+    pub instance_types  : Vec<BehaviorInstanceType>,
+}
+
 pub struct GameRegion {
     pub name            : String,
     pub path            : PathBuf,
@@ -24,6 +36,7 @@ pub struct GameRegion {
     pub data            : GameRegionData,
     pub behaviors       : Vec<GameBehavior>,
     pub displacements   : HashMap<(isize, isize), (usize, usize, usize, TileUsage)>,
+    pub grid            : SpatialGrid,
 }
 
 impl GameRegion {
@@ -42,11 +55,12 @@ impl GameRegion {
                     layer2      : HashMap::new(),
                     layer3      : HashMap::new(),
                     layer4      : HashMap::new(),
-                    id          : thread_rng().gen_range(1..=u32::MAX) as usize,
+                    id          : default_data_id(&path.to_string_lossy()),
                     curr_pos    : (0,0),
                     min_pos     : (10000,10000),
                     max_pos     : (-10000, -10000),
                     areas       : vec![],
+                    instances   : vec![],
                     settings    : PropertySink::new(),
                 });
 
@@ -71,6 +85,7 @@ impl GameRegion {
             data,
             behaviors,
             displacements       : HashMap::new(),
+            grid                : SpatialGrid::new(),
         }
     }
 
@@ -82,11 +97,12 @@ impl GameRegion {
             layer2      : HashMap::new(),
             layer3      : HashMap::new(),
             layer4      : HashMap::new(),
-            id          : thread_rng().gen_range(1..=u32::MAX) as usize,
+            id          : default_data_id(file_name),
             curr_pos    : (0,0),
             min_pos     : (10000,10000),
             max_pos     : (-10000, -10000),
             areas       : vec![],
+            instances   : vec![],
             settings    : PropertySink::new(),
         };
 
@@ -120,6 +136,7 @@ impl GameRegion {
             data,
             behaviors,
             displacements       : HashMap::new(),
+            grid                : SpatialGrid::new(),
         }
     }
 
@@ -301,6 +318,71 @@ impl GameRegion {
         names
     }
 
+    /// Returns the teleport areas of the region which lead to another region
+    pub fn get_portals(&self) -> Vec<RegionPortal> {
+        let mut portals = vec![];
+
+        for (area_index, area) in self.data.areas.iter().enumerate() {
+            if let Some(behavior) = self.behaviors.get(area_index) {
+                let mut ids : Vec<&usize> = behavior.data.nodes.keys().collect();
+                ids.sort();
+
+                for id in ids {
+                    let node = &behavior.data.nodes[id];
+                    if node.behavior_type == BehaviorNodeType::TeleportArea {
+                        if let Some(value) = node.values.get("position") {
+                            let destination = (value.0 as usize, value.1 as isize, value.2 as isize);
+                            let instance_types = Self::get_teleport_instance_types(behavior, *id);
+                            if destination.0 != self.data.id && area.area.is_empty() == false && instance_types.is_empty() == false {
+                                portals.push(RegionPortal { area: area.area.clone(), destination, instance_types });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        portals
+    }
+
+    /// Returns the instance types for which an Enter Area or Inside Area trigger connected to the teleport node fires
+    fn get_teleport_instance_types(behavior: &GameBehavior, teleport_id: usize) -> Vec<BehaviorInstanceType> {
+        let mut instance_types = vec![];
+
+        let mut ids : Vec<&usize> = behavior.data.nodes.keys().collect();
+        ids.sort();
+
+        for id in ids {
+            let node = &behavior.data.nodes[id];
+            if node.behavior_type != BehaviorNodeType::EnterArea && node.behavior_type != BehaviorNodeType::InsideArea {
+                continue;
+            }
+
+            // Follow the connections of the trigger
+            let mut reached = false;
+            let mut visited = vec![*id];
+            let mut index = 0;
+            while index < visited.len() && reached == false {
+                for (source, _, dest, _) in &behavior.data.connections {
+                    if *source == visited[index] && visited.contains(dest) == false {
+                        reached = reached || *dest == teleport_id;
+                        visited.push(*dest);
+                    }
+                }
+                index += 1;
+            }
+
+            if reached {
+                let applies_to = node.values.get("applies_to").map(|value| value.0 as usize).unwrap_or(0);
+                for instance_type in [BehaviorInstanceType::Player, BehaviorInstanceType::NonPlayerCharacter] {
+                    if area_trigger_applies(applies_to, instance_type) && instance_types.contains(&instance_type) == false {
+                        instance_types.push(instance_type);
+                    }
+                }
+            }
+        }
+        instance_types
+    }
+
     /// Rename the region
     pub fn rename(&mut self, name: String) {
         self.name = name.clone();
@@ -323,13 +405,28 @@ pub fn update_region_sink(sink: &mut PropertySink) {
     if sink.contains("lighting") == false {
         sink.push(Property::new_string("lighting".to_string(), "off".to_string()));
     }
+
+    if sink.contains("diagonal_movement") == false {
+        sink.push(Property::new_bool("diagonal_movement".to_string(), false));
+    }
+
+    if sink.contains("simulation") == false {
+        sink.push(Property::new_string("simulation".to_string(), "full".to_string()));
+    }
+
+    if sink.contains("simulation_rate") == false {
+        sink.push(Property::new_int("simulation_rate".to_string(), 4));
+    }
 }
 
 pub fn generate_region_sink_descriptions() -> HashMap<String, Vec<String>> {
     let mut map : HashMap<String, Vec<String>> = HashMap::new();
 
     map.insert("background".to_string(), vec!["The background color of the region".to_string()]);
-    map.insert("lighting".to_string(), vec!["The lighting mode. Use \"off\" for no lighting.".to_string()]);
+    map.insert("lighting".to_string(), vec!["The lighting mode. Use \"off\" for no lighting, \"daylight\" for outdoor regions which get dark at night.".to_string()]);
+    map.insert("diagonal_movement".to_string(), vec!["Allow characters to move diagonally when following a path.".to_string()]);
+    map.insert("simulation".to_string(), vec!["How characters are simulated while no player is in the region.".to_string(), "\"full\" every tick, \"reduced\" every simulation_rate ticks (timers keep running),".to_string(), "\"suspended\" not at all (sleep, Cooldown and respawn delays pause, the clock keeps running).".to_string()]);
+    map.insert("simulation_rate".to_string(), vec!["The tick interval of the \"reduced\" simulation.".to_string()]);
 
     map
 }
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use core_shared::actions::PlayerAction;
+
+/// The recorded input events
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub enum ReplayEventType {
+    PlayerJoined(usize),
+    PlayerLeft(usize),
+    Action(PlayerAction),
+}
+
+/// An input event, applied before the tick with the given tick number
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ReplayEvent {
+    pub tick                    : usize,
+    pub event                   : ReplayEventType,
+}
+
+/// A recorded session, replaying it against the same project reproduces the session tick by tick
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Replay {
+    /// The seed the session ran with
+    pub seed                    : u64,
+    /// The player of the local client which recorded the session, None for sessions recorded by a server
+    #[serde(default)]
+    pub player_id               : Option<usize>,
+    pub events                  : Vec<ReplayEvent>,
+    /// The amount of ticks of the session
+    pub ticks                   : usize,
+}
+
+impl Replay {
+
+    pub fn new(seed: u64, player_id: Option<usize>) -> Self {
+        Self {
+            seed,
+            player_id,
+            events              : vec![],
+            ticks               : 0,
+        }
+    }
+
+    /// The player to follow when replaying, the recording player or the first player which joined
+    pub fn get_player_id(&self) -> Option<usize> {
+        if self.player_id.is_some() {
+            return self.player_id;
+        }
+        for event in &self.events {
+            if let ReplayEventType::PlayerJoined(player_id) = event.event {
+                return Some(player_id);
+            }
+        }
+        None
+    }
+
+    /// Loads a replay file
+    pub fn load_from_path(path: &Path) -> Option<Self> {
+        if let Some(contents) = fs::read_to_string(path).ok() {
+            return serde_json::from_str(&contents).ok();
+        }
+        None
+    }
+
+    /// Writes the replay file
+    pub fn save_to_path(&self, path: &Path) -> bool {
+        if let Some(json) = serde_json::to_string(&self).ok() {
+            return fs::write(path, json).is_ok();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn player_of_the_replay() {
+        let mut replay = Replay::new(1, None);
+        assert_eq!(replay.get_player_id(), None);
+
+        replay.events.push(ReplayEvent { tick: 0, event: ReplayEventType::PlayerJoined(5) });
+        replay.events.push(ReplayEvent { tick: 3, event: ReplayEventType::PlayerJoined(6) });
+        assert_eq!(replay.get_player_id(), Some(5));
+
+        replay.player_id = Some(6);
+        let json = serde_json::to_string(&replay).unwrap();
+        assert_eq!(serde_json::from_str::<Replay>(&json).unwrap().get_player_id(), Some(6));
+
+        // Replays written before the player id was stored still load
+        let json = r#"{"seed":1,"events":[],"ticks":0}"#;
+        assert_eq!(serde_json::from_str::<Replay>(json).unwrap().player_id, None);
+    }
+}
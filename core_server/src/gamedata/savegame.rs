@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use core_shared::asset::TileUsage;
+use core_shared::item::ItemData;
+
+use super::behavior::{BehaviorInstance, SpawnData, GameEvent};
+
+/// A complete snapshot of a running game, restorable into a GameData loaded from the same project
+#[derive(Serialize, Deserialize)]
+pub struct GameSnapshot {
+    /// All instances, their number variables are stored in number_values
+    pub instances               : Vec<BehaviorInstance>,
+
+    /// Player id -> instance index
+    pub player_ids_inst_indices : HashMap<usize, usize>,
+
+    pub game_instance_index     : Option<usize>,
+
+    /// The tile displacements per region id
+    pub displacements           : Vec<(usize, Vec<((isize, isize), (usize, usize, usize, TileUsage))>)>,
+
+    /// The seed of the rng in deterministic mode and its position in the random stream
+    #[serde(default)]
+    pub seed                    : Option<u64>,
+    #[serde(default)]
+    pub rng_word_pos            : u128,
+
+    #[serde(default)]
+    pub tick_count              : usize,
+
+    /// The items lying in the regions
+    #[serde(default)]
+    pub loot                    : Vec<((usize, isize, isize), Vec<ItemData>)>,
+
+    /// The state of the Spawn area nodes, keyed by (region id, area index, node id)
+    #[serde(default)]
+    pub spawns                  : Vec<((usize, usize, usize), SpawnData)>,
+
+    /// The global variables
+    #[serde(default)]
+    pub globals                 : Vec<(String, f64)>,
+
+    /// The events waiting for delivery on the next tick
+    #[serde(default)]
+    pub events                  : Vec<GameEvent>,
+
+    /// The reputations between the factions
+    #[serde(default)]
+    pub factions                : Vec<(String, String, f64)>,
+}
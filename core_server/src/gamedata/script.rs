@@ -1,9 +1,12 @@
 
-use rhai::{ Dynamic };
-use std::{collections::HashMap};
+use rhai::{ Dynamic, Engine };
+use std::collections::{ BTreeSet, HashMap, HashSet };
+use std::rc::Rc;
+use std::cell::RefCell;
 
 use super::behavior::{ BehaviorNodeType, BehaviorType };
 use crate::gamedata::*;
+use crate::gamedata::nodes_utility::{ can_see, get_faction };
 use regex::bytes::Regex;
 
 #[derive(Debug, Clone)]
@@ -29,6 +32,188 @@ impl InstanceVariables {
     }
 }
 
+/// The global number variables, defined by the number variables of the game behavior. All scopes share the same
+/// variables, i.e. globals["alarm"] can be read and written from every expression and script.
+#[derive(Debug, Clone)]
+pub struct GlobalVariables {
+    numbers                     : Rc<RefCell<HashMap<String, f64>>>,
+    changed                     : Rc<RefCell<BTreeSet<String>>>,
+    /// The variables the screen scripts of the clients are allowed to write
+    screen_writable             : Rc<RefCell<HashSet<String>>>,
+}
+
+impl GlobalVariables {
+
+    pub fn new() -> Self {
+        Self {
+            numbers             : Rc::new(RefCell::new(HashMap::new())),
+            changed             : Rc::new(RefCell::new(BTreeSet::new())),
+            screen_writable     : Rc::new(RefCell::new(HashSet::new())),
+        }
+    }
+
+    /// Registers the type and its indexers with the engine
+    pub fn register(engine: &mut Engine) {
+        engine.register_type_with_name::<GlobalVariables>("Globals")
+            .register_indexer_get(GlobalVariables::get_number)
+            .register_indexer_set(GlobalVariables::set_number)
+            .register_indexer_set(GlobalVariables::set_int);
+    }
+
+    fn get_number(&mut self, index: String) -> f64 {
+        self.get(&index).unwrap_or(0.0)
+    }
+
+    fn set_number(&mut self, index: String, value: f64) {
+        self.set(&index, value);
+    }
+
+    fn set_int(&mut self, index: String, value: i64) {
+        self.set(&index, value as f64);
+    }
+
+    /// Returns the value of the variable
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.numbers.borrow().get(name).cloned()
+    }
+
+    /// Sets the value of an existing variable and remembers the change, returns false if the variable does not exist
+    pub fn set(&self, name: &str, value: f64) -> bool {
+        if let Some(v) = self.numbers.borrow_mut().get_mut(name) {
+            if *v != value {
+                *v = value;
+                self.changed.borrow_mut().insert(name.to_string());
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Adds the variable or overwrites its value without reporting a change
+    pub fn define(&self, name: &str, value: f64) {
+        self.numbers.borrow_mut().insert(name.to_string(), value);
+    }
+
+    /// Allows the screen scripts of the clients to write the variable
+    pub fn set_screen_writable(&self, name: &str) {
+        self.screen_writable.borrow_mut().insert(name.to_string());
+    }
+
+    /// True if the screen scripts of the clients are allowed to write the variable
+    pub fn is_screen_writable(&self, name: &str) -> bool {
+        self.screen_writable.borrow().contains(name)
+    }
+
+    /// Removes all variables
+    pub fn clear(&self) {
+        self.numbers.borrow_mut().clear();
+        self.changed.borrow_mut().clear();
+        self.screen_writable.borrow_mut().clear();
+    }
+
+    /// The variables sorted by name
+    pub fn to_vec(&self) -> Vec<(String, f64)> {
+        let mut rc : Vec<(String, f64)> = self.numbers.borrow().iter().map(|(k, v)| (k.clone(), *v)).collect();
+        rc.sort_by(|a, b| a.0.cmp(&b.0));
+        rc
+    }
+
+    /// Returns the names of the variables changed since the last call, sorted by name
+    pub fn take_changed(&self) -> Vec<String> {
+        std::mem::take(&mut *self.changed.borrow_mut()).into_iter().collect()
+    }
+}
+
+/// Characters with a reputation at or below this value are hostile to each other
+pub const HOSTILE_REPUTATION : f64 = -50.0;
+
+/// The reputations between the factions, defined in the game settings and adjustable at runtime. Reputations are
+/// symmetric and range from -100 (hostile) to 100 (allied), members of the same faction are always allied.
+#[derive(Debug, Clone)]
+pub struct Factions {
+    reputations                 : Rc<RefCell<HashMap<(String, String), f64>>>,
+}
+
+impl Factions {
+
+    pub fn new() -> Self {
+        Self {
+            reputations         : Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    /// Registers the type and its functions with the engine
+    pub fn register(engine: &mut Engine) {
+        engine.register_type_with_name::<Factions>("Factions")
+            .register_fn("reputation", |f: &mut Factions, a: &str, b: &str| f.get(a, b))
+            .register_fn("adjust_reputation", |f: &mut Factions, a: &str, b: &str, amount: f64| f.adjust(a, b, amount))
+            .register_fn("adjust_reputation", |f: &mut Factions, a: &str, b: &str, amount: i64| f.adjust(a, b, amount as f64))
+            .register_fn("is_hostile", |f: &mut Factions, a: &str, b: &str| f.is_hostile(a, b));
+    }
+
+    fn key(a: &str, b: &str) -> (String, String) {
+        if a <= b { (a.to_string(), b.to_string()) } else { (b.to_string(), a.to_string()) }
+    }
+
+    /// The reputation between the two factions, characters without a faction are neutral
+    pub fn get(&self, a: &str, b: &str) -> f64 {
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+        if a == b {
+            return 100.0;
+        }
+        self.reputations.borrow().get(&Factions::key(a, b)).cloned().unwrap_or(0.0)
+    }
+
+    /// Sets the reputation between two different factions
+    pub fn set(&self, a: &str, b: &str, value: f64) {
+        if a.is_empty() == false && b.is_empty() == false && a != b {
+            self.reputations.borrow_mut().insert(Factions::key(a, b), value.clamp(-100.0, 100.0));
+        }
+    }
+
+    pub fn adjust(&self, a: &str, b: &str, amount: f64) {
+        self.set(a, b, self.get(a, b) + amount);
+    }
+
+    pub fn is_hostile(&self, a: &str, b: &str) -> bool {
+        a.is_empty() == false && b.is_empty() == false && self.get(a, b) <= HOSTILE_REPUTATION
+    }
+
+    /// Removes all reputations
+    pub fn clear(&self) {
+        self.reputations.borrow_mut().clear();
+    }
+
+    /// The reputations sorted by faction names
+    pub fn to_vec(&self) -> Vec<(String, String, f64)> {
+        let mut rc : Vec<(String, String, f64)> = self.reputations.borrow().iter().map(|(k, v)| (k.0.clone(), k.1.clone(), *v)).collect();
+        rc.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        rc
+    }
+}
+
+/// Parses the reputations of the game settings, "Guards, Bandits, -100" per line, lines starting with // are ignored
+pub fn parse_reputations(text: &str) -> Vec<(String, String, f64)> {
+    let mut rc = vec![];
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        let parts : Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        if parts.len() == 3 {
+            if let Some(value) = parts[2].parse::<f64>().ok() {
+                rc.push((parts[0].to_string(), parts[1].to_string(), value));
+                continue;
+            }
+        }
+        println!("Invalid reputation \"{}\", use the \"Faction, Faction, Reputation\" format", line);
+    }
+    rc
+}
+
 /// Adds the given target variables to the scope
 pub fn add_target_to_scope(instance_index: usize, data: &mut GameData) {
     if let Some(target_index) = data.instances[instance_index].target_instance_index {
@@ -53,6 +238,16 @@ pub fn add_target_to_scope(instance_index: usize, data: &mut GameData) {
         }
 
         data.scopes[instance_index].set_value("target", target);
+
+        // Can the instance see its target ?
+        let visible = can_see(instance_index, target_index, f64::MAX, 360.0, data);
+        data.scopes[instance_index].set_value("target_visible", visible);
+
+        // The faction of the target and if it is hostile to us
+        let faction = get_faction(instance_index, data);
+        let target_faction = get_faction(target_index, data);
+        data.scopes[instance_index].set_value("hostile_to_target", data.factions.is_hostile(&faction, &target_faction));
+        data.scopes[instance_index].set_value("target_faction", target_faction);
     }
 }
 
@@ -335,3 +530,77 @@ pub fn eval_dynamic_expression_instance_editor(instance_index: usize, id: (Behav
     }
     false
 }
+
+/// Evaluates a boolean expression of an area node in the area scope.
+/// The ids of area behaviors are only unique per region, so the compiled expression is not cached.
+pub fn eval_bool_expression_area(region_id: usize, id: (usize, usize, String), data: &mut GameData) -> Option<bool> {
+    if let Some(value) = get_node_value((id.0, id.1, &id.2), data, BehaviorType::Regions, region_id) {
+        let r = data.engine.eval_expression_with_scope::<bool>(&mut data.area_scope, value.4.as_str());
+        if r.is_ok() {
+            return Some(r.unwrap());
+        } else {
+            println!("{:?}", r);
+        }
+    }
+    None
+}
+
+/// Evaluates a numerical expression of an area node in the area scope.
+pub fn eval_number_expression_area(region_id: usize, id: (usize, usize, String), data: &mut GameData) -> Option<f64> {
+    if let Some(value) = get_node_value((id.0, id.1, &id.2), data, BehaviorType::Regions, region_id) {
+        let r = data.engine.eval_expression_with_scope::<Dynamic>(&mut data.area_scope, value.4.as_str());
+        if r.is_ok() {
+            let nn = r.unwrap();
+            if let Some(n) = nn.as_float().ok() {
+                return Some(n);
+            }
+            if let Some(n) = nn.as_int().ok() {
+                return Some(n as f64);
+            }
+        } else {
+            println!("{:?}", r);
+        }
+    }
+    None
+}
+
+/// Evaluates a script of an area node in the area scope.
+pub fn eval_dynamic_script_area(region_id: usize, id: (usize, usize, String), data: &mut GameData) -> bool {
+    if let Some(value) = get_node_value((id.0, id.1, &id.2), data, BehaviorType::Regions, region_id) {
+        let r = data.engine.eval_with_scope::<Dynamic>(&mut data.area_scope, value.4.as_str());
+        if r.is_ok() {
+            return true;
+        } else {
+            println!("{:?}", r);
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_globals_are_reported_once() {
+        let globals = GlobalVariables::new();
+        globals.define("a", 0.0);
+        globals.define("b", 0.0);
+        globals.set("a", 1.0);
+        globals.set("b", 1.0);
+        globals.set("a", 2.0);
+        assert_eq!(globals.take_changed(), vec!["a".to_string(), "b".to_string()]);
+        assert!(globals.take_changed().is_empty());
+    }
+
+    #[test]
+    fn only_defined_globals_can_be_set() {
+        let globals = GlobalVariables::new();
+        globals.define("a", 0.0);
+        assert!(globals.set("b", 1.0) == false);
+        assert_eq!(globals.get("b"), None);
+        assert!(globals.is_screen_writable("a") == false);
+        globals.set_screen_writable("a");
+        assert!(globals.is_screen_writable("a"));
+    }
+}
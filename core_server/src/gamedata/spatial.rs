@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+/// The size of a grid cell in tiles
+const CELL_SIZE : isize = 8;
+
+/// A spatial grid of the instances inside a region, answers position queries without looping over all instances
+#[derive(Clone, Debug, Default)]
+pub struct SpatialGrid {
+    cells                       : HashMap<(isize, isize), Vec<(usize, (isize, isize))>>,
+    count                       : usize,
+}
+
+impl SpatialGrid {
+
+    pub fn new() -> Self {
+        Self {
+            cells               : HashMap::new(),
+            count               : 0,
+        }
+    }
+
+    /// Removes all instances
+    pub fn clear(&mut self) {
+        self.cells = HashMap::new();
+        self.count = 0;
+    }
+
+    /// The amount of instances in the grid
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the grid contains no instances
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Adds the instance at the given position, does nothing if it is already there
+    pub fn add(&mut self, index: usize, pos: (isize, isize)) {
+        let cell = get_cell(pos);
+        if let Some(list) = self.cells.get_mut(&cell) {
+            if list.contains(&(index, pos)) {
+                return;
+            }
+            list.push((index, pos));
+        } else {
+            self.cells.insert(cell, vec![(index, pos)]);
+        }
+        self.count += 1;
+    }
+
+    /// Removes the instance from the given position, does nothing if it is not there
+    pub fn remove(&mut self, index: usize, pos: (isize, isize)) {
+        let cell = get_cell(pos);
+        if let Some(list) = self.cells.get_mut(&cell) {
+            if let Some(i) = list.iter().position(|entry| *entry == (index, pos)) {
+                list.swap_remove(i);
+                if list.is_empty() {
+                    self.cells.remove(&cell);
+                }
+                self.count -= 1;
+            }
+        }
+    }
+
+    /// Returns the instances at the given tile, sorted by index
+    pub fn at(&self, pos: (isize, isize)) -> Vec<usize> {
+        let mut rc = vec![];
+        if let Some(list) = self.cells.get(&get_cell(pos)) {
+            for (index, p) in list {
+                if *p == pos {
+                    rc.push(*index);
+                }
+            }
+        }
+        rc.sort();
+        rc
+    }
+
+    /// Returns true if an instance is located at the given tile
+    pub fn contains(&self, pos: (isize, isize)) -> bool {
+        if let Some(list) = self.cells.get(&get_cell(pos)) {
+            return list.iter().any(|(_, p)| *p == pos);
+        }
+        false
+    }
+
+    /// Returns the instances within the given distance of the tile, sorted by index
+    pub fn within(&self, pos: (isize, isize), radius: f64) -> Vec<usize> {
+        let mut lists = vec![];
+        let r = radius.max(0.0).ceil();
+        let span = 2.0 * r / CELL_SIZE as f64 + 2.0;
+
+        if span * span > self.cells.len() as f64 {
+            // The radius covers more cells than are occupied, also keeps huge radii from overflowing
+            lists.extend(self.cells.values());
+        } else {
+            let r = r as isize;
+            let min = get_cell((pos.0 - r, pos.1 - r));
+            let max = get_cell((pos.0 + r, pos.1 + r));
+
+            for cy in min.1..=max.1 {
+                for cx in min.0..=max.0 {
+                    if let Some(list) = self.cells.get(&(cx, cy)) {
+                        lists.push(list);
+                    }
+                }
+            }
+        }
+
+        let mut rc = vec![];
+        for (index, p) in lists.into_iter().flatten() {
+            let dx = (p.0 - pos.0) as f64;
+            let dy = (p.1 - pos.1) as f64;
+            if (dx * dx + dy * dy).sqrt() <= radius {
+                rc.push(*index);
+            }
+        }
+        rc.sort();
+        rc
+    }
+
+    /// Returns all instances in the grid, sorted by index
+    pub fn all(&self) -> Vec<usize> {
+        let mut rc : Vec<usize> = self.cells.values().flat_map(|list| list.iter().map(|(index, _)| *index)).collect();
+        rc.sort();
+        rc
+    }
+}
+
+/// The cell of the given tile
+fn get_cell(pos: (isize, isize)) -> (isize, isize) {
+    (pos.0.div_euclid(CELL_SIZE), pos.1.div_euclid(CELL_SIZE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn within_radius() {
+        let mut grid = SpatialGrid::new();
+        grid.add(0, (0, 0));
+        grid.add(1, (3, 4));
+        grid.add(2, (-20, 7));
+        grid.add(3, (100, 100));
+
+        assert_eq!(grid.within((0, 0), 0.0), vec![0]);
+        assert_eq!(grid.within((0, 0), 5.0), vec![0, 1]);
+        assert_eq!(grid.within((-18, 7), 2.0), vec![2]);
+        assert_eq!(grid.within((0, 0), -1.0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn within_huge_radius() {
+        let mut grid = SpatialGrid::new();
+        grid.add(0, (0, 0));
+        grid.add(1, (isize::MAX / 2, -5));
+
+        assert_eq!(grid.within((0, 0), f64::MAX), vec![0, 1]);
+        assert_eq!(grid.within((0, 0), f64::INFINITY), vec![0, 1]);
+        assert_eq!(grid.within((0, 0), 1_000_000.0), vec![0]);
+    }
+}
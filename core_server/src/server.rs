@@ -0,0 +1,299 @@
+use std::io::ErrorKind;
+use std::net::{TcpListener, ToSocketAddrs, SocketAddr};
+use std::time::{Duration, Instant};
+
+use core_shared::actions::PlayerAction;
+use core_shared::codec::Codec;
+use core_shared::network::{Connection, ClientHello, ServerWelcome};
+
+use log::{ info, warn };
+
+use crate::gamedata::GameData;
+
+/// Connections which do not send their ClientHello within this time are dropped
+pub const HELLO_TIMEOUT         : Duration = Duration::from_secs(5);
+
+/// New connections are refused while this many connections wait for their ClientHello
+pub const MAX_PENDING           : usize = 64;
+
+/// A connected client and the player id assigned to it
+struct ClientConnection {
+    player_id                   : usize,
+    codec                       : Codec,
+    connection                  : Connection,
+}
+
+/// Accepts TCP connections and routes PlayerAction frames into and GameUpdate frames out of a GameData.
+/// Clients first send a ClientHello with the codec they want to use, the server answers with a ServerWelcome.
+pub struct GameServer {
+    listener                    : TcpListener,
+    clients                     : Vec<ClientConnection>,
+
+    /// Connections which did not send their ClientHello yet and when they connected
+    pending                     : Vec<(Connection, SocketAddr, Instant)>,
+
+    /// The codecs the server accepts, clients requesting other codecs get the first one
+    pub codecs                  : Vec<Codec>,
+
+    /// How long a new connection may take to send its ClientHello
+    pub hello_timeout           : Duration,
+    /// The maximum amount of connections waiting for their ClientHello
+    pub max_pending             : usize,
+
+    next_player_id              : usize,
+}
+
+impl GameServer {
+
+    /// Binds the server to the given address, use port 0 to let the OS pick a free port
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            clients             : vec![],
+            pending             : vec![],
+            codecs              : vec![Codec::Binary, Codec::Json],
+            hello_timeout       : HELLO_TIMEOUT,
+            max_pending         : MAX_PENDING,
+            next_player_id      : 1,
+        })
+    }
+
+    /// The address the server listens on
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// The amount of connected clients
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Accepts new connections and hands received actions to the game
+    pub fn poll(&mut self, data: &mut GameData) {
+
+        // Accept new connections, they have to say hello first
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    if self.pending.len() >= self.max_pending {
+                        warn!("Refused connection from {}, too many pending connections", addr);
+                    } else
+                    if let Some(connection) = Connection::new(stream).ok() {
+                        self.pending.push((connection, addr, Instant::now()));
+                    }
+                },
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    warn!("Failed to accept connection: {}", e);
+                    break;
+                }
+            }
+        }
+
+        // Negotiate the codec and create a player instance for each new client
+        let mut index = 0;
+        while index < self.pending.len() {
+            let mut frames = self.pending[index].0.receive();
+            if frames.is_empty() == false {
+                let (mut connection, addr, _) = self.pending.remove(index);
+
+                if let Some(hello) = serde_json::from_slice::<ClientHello>(&frames.remove(0)).ok() {
+                    let mut codec = self.codecs[0];
+                    if self.codecs.contains(&hello.codec) {
+                        codec = hello.codec;
+                    }
+
+                    let player_id = self.next_player_id;
+                    self.next_player_id += 1;
+
+                    _ = data.create_player_instance(player_id);
+
+                    if let Some(welcome) = serde_json::to_vec(&ServerWelcome { player_id, codec }).ok() {
+                        connection.send(&welcome);
+                    }
+                    info!("Player {} connected from {} ({:?})", player_id, addr, codec);
+
+                    let mut client = ClientConnection { player_id, codec, connection };
+                    for frame in frames {
+                        Self::execute_frame(&mut client, &frame, data);
+                    }
+                    self.clients.push(client);
+                } else {
+                    warn!("Invalid hello from {}", addr);
+                }
+            } else
+            if self.pending[index].0.closed {
+                self.pending.remove(index);
+            } else
+            if self.pending[index].2.elapsed() > self.hello_timeout {
+                let (mut connection, addr, _) = self.pending.remove(index);
+                connection.close();
+                warn!("No hello from {}, dropping the connection", addr);
+            } else {
+                index += 1;
+            }
+        }
+
+        // Read the actions of the clients
+        for client in &mut self.clients {
+            for frame in client.connection.receive() {
+                Self::execute_frame(client, &frame, data);
+            }
+        }
+
+        self.remove_closed_clients(data);
+    }
+
+    /// Sends the updates of the last tick to the clients
+    pub fn send_updates(&mut self, data: &mut GameData) {
+        for client in &mut self.clients {
+            if let Some(update) = data.poll_update_encoded(client.player_id, client.codec) {
+                client.connection.send(&update);
+            }
+        }
+
+        self.remove_closed_clients(data);
+    }
+
+    /// Polls the connections, ticks the game and sends the updates
+    pub fn tick(&mut self, data: &mut GameData) {
+        self.poll(data);
+        data.tick();
+        self.send_updates(data);
+    }
+
+    /// Decodes an action frame of the client and hands it to the game
+    fn execute_frame(client: &mut ClientConnection, frame: &[u8], data: &mut GameData) {
+        if let Some(mut action) = client.codec.decode::<PlayerAction>(frame) {
+            // Save slots affect the whole world, they are not available to network clients
+            if action.action == "save" || action.action == "load" {
+                return;
+            }
+            // Clients can only act for their own player
+            action.player_id = client.player_id;
+            data.execute_instance_action(action);
+        }
+    }
+
+    /// Removes disconnected clients and their player instances
+    fn remove_closed_clients(&mut self, data: &mut GameData) {
+        self.clients.retain(|client| {
+            if client.connection.closed {
+                info!("Player {} disconnected", client.player_id);
+                data.remove_player_instance(client.player_id);
+                return false;
+            }
+            true
+        });
+    }
+}
+
+/// The tests use the game of the repository, with embedded binaries it would be loaded from the embedded files
+#[cfg(all(test, not(feature = "embed_binaries")))]
+mod tests {
+    use super::*;
+    use core_shared::actions::PlayerDirection;
+    use core_shared::network::NetworkClient;
+    use core_shared::update::GameUpdate;
+    use std::net::TcpStream;
+
+    #[test]
+    fn loopback() {
+        let mut data = GameData::load_from_path(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."));
+        data.startup_server();
+
+        let mut server = GameServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // Connecting waits for the welcome, the server has to be polled meanwhile
+        let handle = std::thread::spawn(move || {
+            let a = NetworkClient::connect(addr, Codec::Binary).unwrap();
+            let b = NetworkClient::connect(addr, Codec::Json).unwrap();
+            (a, b)
+        });
+        while handle.is_finished() == false {
+            server.poll(&mut data);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let (mut a, mut b) = handle.join().unwrap();
+
+        assert_eq!(server.client_count(), 2);
+        assert!(a.player_id != b.player_id);
+        assert_eq!((a.codec, b.codec), (Codec::Binary, Codec::Json));
+
+        let index_a = data.player_ids_inst_indices[&a.player_id];
+        let index_b = data.player_ids_inst_indices[&b.player_id];
+        assert!(index_a != index_b);
+
+        let start_a = data.instances[index_a].position;
+        let start_b = data.instances[index_b].position;
+
+        // The player id of the action is replaced by the one of the connection
+        a.send_player_action(&PlayerAction { action: "onMove".to_string(), direction: PlayerDirection::East, player_id: b.player_id, text: "".to_string() });
+
+        let mut state_a = GameUpdate::new();
+        let mut state_b = GameUpdate::new();
+        let mut updates = (0, 0);
+
+        for _ in 0..200 {
+            server.tick(&mut data);
+            std::thread::sleep(Duration::from_millis(5));
+
+            while let Some(update) = a.poll_update() {
+                state_a.apply(&update);
+                updates.0 += 1;
+            }
+            while let Some(update) = b.poll_update() {
+                state_b.apply(&update);
+                updates.1 += 1;
+            }
+            if state_a.position.is_some() && state_a.position != start_a && state_b.position.is_some() {
+                break;
+            }
+        }
+
+        assert!(updates.0 > 0 && updates.1 > 0);
+        assert!(data.instances[index_a].position != start_a);
+        assert_eq!(data.instances[index_b].position, start_b);
+        assert_eq!(state_a.position, data.instances[index_a].position);
+        assert_eq!(state_b.position, start_b);
+
+        // Closing a client removes its player instance
+        let player_b = b.player_id;
+        drop(b);
+        for _ in 0..50 {
+            server.tick(&mut data);
+            if server.client_count() == 1 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(server.client_count(), 1);
+        assert!(data.player_ids_inst_indices.contains_key(&a.player_id));
+        assert!(data.player_ids_inst_indices.contains_key(&player_b) == false);
+    }
+
+    #[test]
+    fn pending_connections_are_limited() {
+        let mut data = GameData::new();
+
+        let mut server = GameServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        server.max_pending = 2;
+        server.hello_timeout = Duration::from_millis(50);
+
+        // None of these say hello
+        let _streams : Vec<TcpStream> = (0..3).map(|_| TcpStream::connect(addr).unwrap()).collect();
+        std::thread::sleep(Duration::from_millis(20));
+        server.poll(&mut data);
+        assert_eq!(server.pending.len(), 2);
+
+        std::thread::sleep(Duration::from_millis(100));
+        server.poll(&mut data);
+        assert!(server.pending.is_empty());
+        assert_eq!(server.client_count(), 0);
+    }
+}
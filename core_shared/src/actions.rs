@@ -16,6 +16,7 @@ pub enum PlayerDirection {
     West,
     Up,
     Down,
+    None,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
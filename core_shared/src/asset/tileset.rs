@@ -8,7 +8,8 @@ use std::fs::metadata;
 
 use std::collections::HashMap;
 use std::path::PathBuf;
-use rand::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 #[cfg(feature = "embed_binaries")]
 use core_embed_binaries::Embedded;
@@ -35,10 +36,20 @@ pub struct Tile {
     pub anim_tiles          : Vec<(usize, usize)>,
     pub tags                : String,
     pub role                : usize,
+    /// The cost of walking over the tile, 0 uses the default cost of the tile usage
+    #[serde(default)]
+    pub movement_cost       : usize,
 }
 
 // TileMap implementation
 
+/// The id of a tilemap without settings, derived from its name so that it is the same on every run
+fn default_tilemap_id(name: &str) -> usize {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    (hasher.finish() % u32::MAX as u64) as usize + 1
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct TileMapSettings {
     pub grid_size       : usize,
@@ -84,7 +95,7 @@ impl TileMap {
 
         // Construct the json settings
         let settings = serde_json::from_str(&contents)
-            .unwrap_or(TileMapSettings { grid_size: 16, tiles: HashMap::new(), id: thread_rng().gen_range(1..=u32::MAX) as usize, default_tile: None } );
+            .unwrap_or(TileMapSettings { grid_size: 16, tiles: HashMap::new(), id: default_tilemap_id(name), default_tile: None } );
 
         TileMap {
             base_path       : base_path.clone(),
@@ -131,7 +142,7 @@ impl TileMap {
 
         // Construct the json settings
         let settings = serde_json::from_str(&contents)
-            .unwrap_or(TileMapSettings { grid_size: 16, tiles: HashMap::new(), id: thread_rng().gen_range(1..=u32::MAX) as usize, default_tile: None } );
+            .unwrap_or(TileMapSettings { grid_size: 16, tiles: HashMap::new(), id: default_tilemap_id(name), default_tile: None } );
 
         TileMap {
             base_path       : PathBuf::new(),
@@ -146,9 +157,9 @@ impl TileMap {
     /// Get the tile for the given id
     pub fn get_tile(&self, tile_id: &(usize, usize)) -> Tile {
         if let Some(t) = self.settings.tiles.get(&tile_id) {
-            Tile { usage: t.usage.clone(), anim_tiles: t.anim_tiles.clone(), tags: t.tags.clone(), role: t.role.clone() }
+            Tile { usage: t.usage.clone(), anim_tiles: t.anim_tiles.clone(), tags: t.tags.clone(), role: t.role.clone(), movement_cost: t.movement_cost }
         } else {
-            Tile { usage: TileUsage::Environment, anim_tiles: vec![], tags: "".to_string(), role: 0 }
+            Tile { usage: TileUsage::Environment, anim_tiles: vec![], tags: "".to_string(), role: 0, movement_cost: 0 }
         }
     }
 
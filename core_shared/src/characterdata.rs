@@ -1,7 +1,7 @@
 
 
 use serde::{Deserialize, Serialize};
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct CharacterData {
     pub name            : String,
     pub id              : usize,
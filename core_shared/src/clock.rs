@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+/// The minutes of a game day
+pub const MINUTES_PER_DAY : usize = 24 * 60;
+
+/// The in-game clock, the time is derived from the tick count of the server
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct GameClock {
+    /// How many ticks make up one game minute
+    pub ticks_per_minute        : usize,
+
+    /// The time of day the game starts at, in minutes since midnight
+    pub start_minutes           : usize,
+}
+
+impl GameClock {
+
+    pub fn new() -> Self {
+        Self {
+            ticks_per_minute    : 4,
+            start_minutes       : 8 * 60,
+        }
+    }
+
+    /// The total game minutes passed at the given tick, including the start time
+    pub fn total_minutes(&self, tick_count: usize) -> usize {
+        self.start_minutes + tick_count / self.ticks_per_minute.max(1)
+    }
+
+    /// The minutes since midnight at the given tick
+    pub fn minutes_of_day(&self, tick_count: usize) -> usize {
+        self.total_minutes(tick_count) % MINUTES_PER_DAY
+    }
+
+    /// The day at the given tick, starting with 0
+    pub fn day(&self, tick_count: usize) -> usize {
+        self.total_minutes(tick_count) / MINUTES_PER_DAY
+    }
+}
+
+/// Parses a time of day in the "HH:MM" or "HH" format into minutes since midnight, "24:00" is midnight
+pub fn parse_time_of_day(text: &str) -> Option<usize> {
+    let mut parts = text.trim().split(':');
+    let hours = parts.next()?.trim().parse::<usize>().ok()?;
+    let minutes = match parts.next() {
+        Some(m) => m.trim().parse::<usize>().ok()?,
+        None => 0,
+    };
+    if hours > 24 || (hours == 24 && minutes > 0) || minutes > 59 || parts.next().is_some() {
+        return None;
+    }
+    Some((hours * 60 + minutes) % MINUTES_PER_DAY)
+}
+
+/// Returns true if the time of day lies in the range, ranges with from > to wrap around midnight
+pub fn is_time_in_range(minutes: usize, from: usize, to: usize) -> bool {
+    if from <= to {
+        minutes >= from && minutes < to
+    } else {
+        minutes >= from || minutes < to
+    }
+}
+
+/// The amount of daylight for the given minutes since midnight, 0.0 at night and 1.0 during the day.
+/// Dawn lasts from 05:00 to 07:00, dusk from 19:00 to 21:00.
+pub fn get_daylight(minutes: usize) -> f64 {
+    let hours = (minutes % MINUTES_PER_DAY) as f64 / 60.0;
+    if hours < 5.0 || hours >= 21.0 {
+        0.0
+    } else
+    if hours < 7.0 {
+        (hours - 5.0) / 2.0
+    } else
+    if hours < 19.0 {
+        1.0
+    } else {
+        1.0 - (hours - 19.0) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_valid_times() {
+        assert_eq!(parse_time_of_day("00:00"), Some(0));
+        assert_eq!(parse_time_of_day("8"), Some(8 * 60));
+        assert_eq!(parse_time_of_day(" 18:30 "), Some(18 * 60 + 30));
+        assert_eq!(parse_time_of_day("23:59"), Some(MINUTES_PER_DAY - 1));
+        assert_eq!(parse_time_of_day("24:00"), Some(0));
+        assert_eq!(parse_time_of_day("24"), Some(0));
+    }
+
+    #[test]
+    fn parse_invalid_times() {
+        assert_eq!(parse_time_of_day(""), None);
+        assert_eq!(parse_time_of_day("24:30"), None);
+        assert_eq!(parse_time_of_day("25:00"), None);
+        assert_eq!(parse_time_of_day("12:60"), None);
+        assert_eq!(parse_time_of_day("12:00:00"), None);
+        assert_eq!(parse_time_of_day("noon"), None);
+        assert_eq!(parse_time_of_day("-1:00"), None);
+    }
+
+    #[test]
+    fn time_in_range() {
+        let from = parse_time_of_day("08:00").unwrap();
+        let to = parse_time_of_day("18:00").unwrap();
+        assert!(is_time_in_range(from, from, to));
+        assert!(is_time_in_range(12 * 60, from, to));
+        assert!(is_time_in_range(to - 1, from, to));
+        assert!(is_time_in_range(to, from, to) == false);
+        assert!(is_time_in_range(7 * 60, from, to) == false);
+    }
+
+    #[test]
+    fn time_in_range_wraps_around_midnight() {
+        let from = parse_time_of_day("22:00").unwrap();
+        let to = parse_time_of_day("06:00").unwrap();
+        assert!(is_time_in_range(23 * 60, from, to));
+        assert!(is_time_in_range(0, from, to));
+        assert!(is_time_in_range(5 * 60 + 59, from, to));
+        assert!(is_time_in_range(6 * 60, from, to) == false);
+        assert!(is_time_in_range(12 * 60, from, to) == false);
+
+        // Until midnight
+        let to = parse_time_of_day("24:00").unwrap();
+        assert!(is_time_in_range(23 * 60 + 59, from, to));
+        assert!(is_time_in_range(0, from, to) == false);
+    }
+}
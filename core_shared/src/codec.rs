@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// The wire formats for client / server traffic
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Codec {
+    /// Human readable JSON, useful for debugging
+    Json,
+    /// Compact binary format (bincode)
+    Binary,
+}
+
+impl Codec {
+
+    /// Encodes the given value
+    pub fn encode<T: Serialize>(&self, value: &T) -> Option<Vec<u8>> {
+        match self {
+            Codec::Json => serde_json::to_vec(value).ok(),
+            Codec::Binary => bincode::serialize(value).ok(),
+        }
+    }
+
+    /// Decodes a value from the given bytes
+    pub fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Option<T> {
+        match self {
+            Codec::Json => serde_json::from_slice(bytes).ok(),
+            Codec::Binary => bincode::deserialize(bytes).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::{ PlayerAction, PlayerDirection };
+    use crate::asset::TileUsage;
+    use crate::characterdata::CharacterData;
+    use crate::item::ItemData;
+    use crate::light::{ Light, LightType };
+    use crate::message::{ DialogData, MessageData, MessageType };
+    use crate::property::{ Property, PropertySink };
+    use crate::quest::QuestData;
+    use crate::regiondata::{ GameRegionData, RegionArea, RegionInstance };
+    use crate::update::GameUpdate;
+    use std::collections::HashMap;
+
+    const CODECS : [Codec; 2] = [Codec::Json, Codec::Binary];
+
+    fn character(id: usize, x: isize) -> CharacterData {
+        CharacterData { name: format!("Character {}", id), id, index: id, position: (1, x, 2), tile: (0, 3, 4) }
+    }
+
+    fn item(id: usize, name: &str) -> ItemData {
+        ItemData { id, item_id: 7, name: name.to_string(), tile: Some((0, 1, 2)) }
+    }
+
+    fn region() -> GameRegionData {
+        let mut layers = vec![];
+        for layer in 0..4 {
+            let mut tiles = HashMap::new();
+            tiles.insert((layer, 0), (0, 1, 2, TileUsage::Environment));
+            tiles.insert((-3, layer), (1, 4, 5, TileUsage::EnvBlocking));
+            tiles.insert((7, -layer - 1), (2, 0, 1, TileUsage::Water));
+            layers.push(tiles);
+        }
+
+        let mut settings = PropertySink::new();
+        settings.push(Property::new_int("lighting".to_string(), 3));
+        settings.push(Property::new_float("speed".to_string(), 0.5));
+        settings.push(Property::new_string("simulation".to_string(), "reduced".to_string()));
+        settings.push(Property::new_bool("diagonal_movement".to_string(), true));
+        settings.push(Property::new_color("background".to_string(), "#102030".to_string()));
+
+        GameRegionData {
+            layer4      : layers.pop().unwrap(),
+            layer3      : layers.pop().unwrap(),
+            layer2      : layers.pop().unwrap(),
+            layer1      : layers.pop().unwrap(),
+            id          : 42,
+            curr_pos    : (1, -1),
+            min_pos     : (-3, -4),
+            max_pos     : (7, 3),
+            areas       : vec![RegionArea { name: "Castle".to_string(), id: 5, area: vec![(0, 0), (1, 0), (-1, 2)], behavior: 9 }],
+            instances   : vec![
+                RegionInstance { behavior: 11, position: (2, 3), name: None, number_values: vec![], faction: None },
+                RegionInstance { behavior: 12, position: (-2, 0), name: Some("Guard".to_string()), number_values: vec![("HP".to_string(), 25.0), ("level".to_string(), -1.5)], faction: Some("Guards".to_string()) },
+            ],
+            settings,
+        }
+    }
+
+    fn keyframe() -> GameUpdate {
+        let mut update = GameUpdate::new();
+        update.position = Some((1, 5, 6));
+        update.tile = Some((0, 3, 4));
+        update.screen = Some("fn draw() {}".to_string());
+        update.region = Some(region());
+        update.lights = vec![Light::new(LightType::PointLight, (2, 3), 200)];
+        update.displacements.insert((4, 5), (0, 1, 1, TileUsage::Environment));
+        update.displacements.insert((-1, 2), (0, 2, 1, TileUsage::EnvBlocking));
+        update.characters = vec![character(1, 5), character(2, 8)];
+        update.loot = vec![((3, 3), item(10, "Sword"))];
+        update.inventory = vec![item(11, "Torch"), item(12, "Key")];
+        update.messages = vec![MessageData { message_type: MessageType::Say, message: "Hello".to_string(), from: "Moody".to_string() }];
+        update.audio = vec!["door.wav".to_string()];
+        update.time = 12 * 60 + 30;
+        update.globals = vec![("gold".to_string(), 10.0), ("level".to_string(), -1.5)];
+        update.dialog = Some(DialogData { from: "Moody".to_string(), text: "Need work?".to_string(), choices: vec!["Yes".to_string(), "No".to_string()] });
+        update.journal = vec![QuestData { name: "Rats".to_string(), description: "Clear the cellar".to_string(), objective: "Kill 5 rats".to_string(), progress: 2, required: 5, completed: false }];
+        update.party = vec![character(3, 6)];
+        update
+    }
+
+    fn round_trip(update: &GameUpdate) {
+        for codec in CODECS {
+            let bytes = codec.encode(update).unwrap();
+            let decoded : GameUpdate = codec.decode(&bytes).unwrap();
+            assert!(decoded == *update, "{:?} round trip changed the update", codec);
+        }
+    }
+
+    #[test]
+    fn game_update_keyframe() {
+        round_trip(&keyframe());
+    }
+
+    #[test]
+    fn game_update_delta() {
+        let prev = keyframe();
+
+        let mut next = keyframe();
+        next.characters = vec![character(1, 6)];
+        next.displacements.remove(&(-1, 2));
+        next.loot = vec![];
+        next.inventory.push(item(13, "Coin"));
+        next.globals[0].1 = 11.0;
+        next.dialog = None;
+        next.journal[0].progress = 3;
+        next.party = vec![];
+
+        let delta = next.diff(&prev);
+        assert!(delta.keyframe == false);
+        assert_eq!(delta.removed_characters, vec![2]);
+        assert_eq!(delta.removed_displacements, vec![(-1, 2)]);
+        assert!(delta.loot_changed && delta.inventory_changed && delta.globals_changed && delta.journal_changed && delta.party_changed);
+        assert!(delta.lights_changed == false);
+
+        round_trip(&delta);
+    }
+
+    #[test]
+    fn player_action() {
+        for direction in [PlayerDirection::North, PlayerDirection::East, PlayerDirection::South, PlayerDirection::West, PlayerDirection::Up, PlayerDirection::Down, PlayerDirection::None] {
+            let action = PlayerAction { action: "onMove".to_string(), direction, player_id: 131313, text: "choice 1".to_string() };
+            for codec in CODECS {
+                let bytes = codec.encode(&direction).unwrap();
+                assert_eq!(codec.decode::<PlayerDirection>(&bytes), Some(direction));
+
+                let bytes = codec.encode(&action).unwrap();
+                assert_eq!(codec.decode::<PlayerAction>(&bytes), Some(action.clone()));
+            }
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// An item instance, either lying in a region or carried in an inventory
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ItemData {
+    /// The unique id of this instance
+    pub id              : usize,
+    /// The id of the item behavior
+    pub item_id         : usize,
+    pub name            : String,
+    pub tile            : Option<(usize, usize, usize)>,
+}
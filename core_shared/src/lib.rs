@@ -5,4 +5,10 @@ pub mod characterdata;
 pub mod update;
 pub mod message;
 pub mod property;
-pub mod light;
\ No newline at end of file
+pub mod light;
+pub mod network;
+pub mod codec;
+pub mod item;
+pub mod clock;
+pub mod quest;
+
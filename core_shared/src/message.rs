@@ -8,6 +8,7 @@ pub enum MessageType {
     Private,
     Debug,
     Error,
+    Party,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
@@ -16,3 +17,12 @@ pub struct MessageData {
     pub message             : String,
     pub from                : String,
 }
+
+/// The current line of a conversation and the choices the player can pick from
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct DialogData {
+    /// The name of the character the player talks to
+    pub from                : String,
+    pub text                : String,
+    pub choices             : Vec<String>,
+}
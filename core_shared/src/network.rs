@@ -0,0 +1,254 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::actions::PlayerAction;
+use crate::codec::Codec;
+use crate::update::GameUpdate;
+
+/// Frames larger than this are considered a protocol error
+pub const MAX_FRAME_SIZE        : usize = 16 * 1024 * 1024;
+
+/// A peer which lets more than this amount of bytes queue up for sending is disconnected
+pub const MAX_OUTGOING_SIZE     : usize = 4 * MAX_FRAME_SIZE;
+
+/// How long a client waits for the connection and the welcome of the server
+pub const CONNECT_TIMEOUT       : Duration = Duration::from_secs(10);
+
+/// The first frame a client sends after connecting (always JSON), requests the codec for all following frames
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ClientHello {
+    pub codec                   : Codec,
+}
+
+/// The answer of the server to the ClientHello (always JSON)
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ServerWelcome {
+    pub player_id               : usize,
+    /// The codec used by both sides from now on
+    pub codec                   : Codec,
+}
+
+/// A non-blocking TCP connection exchanging length prefixed frames (u32 big endian length + payload)
+pub struct Connection {
+    stream                      : TcpStream,
+    incoming                    : Vec<u8>,
+    outgoing                    : Vec<u8>,
+
+    /// Set when the peer disconnected or violated the protocol
+    pub closed                  : bool,
+}
+
+impl Connection {
+
+    pub fn new(stream: TcpStream) -> std::io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        _ = stream.set_nodelay(true);
+
+        Ok(Self {
+            stream,
+            incoming            : vec![],
+            outgoing            : vec![],
+            closed              : false,
+        })
+    }
+
+    /// Queues a frame for sending and tries to flush it. Closes the connection if the peer does not read
+    /// its data and the queue would grow beyond MAX_OUTGOING_SIZE.
+    pub fn send(&mut self, payload: &[u8]) {
+        if self.closed {
+            return;
+        }
+        if self.outgoing.len() + 4 + payload.len() > MAX_OUTGOING_SIZE {
+            self.close();
+            return;
+        }
+        self.outgoing.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        self.outgoing.extend_from_slice(payload);
+        self.flush();
+    }
+
+    /// Closes the connection, queued data is dropped
+    pub fn close(&mut self) {
+        _ = self.stream.shutdown(Shutdown::Both);
+        self.outgoing = vec![];
+        self.closed = true;
+    }
+
+    /// Writes as much of the queued data as the socket accepts
+    pub fn flush(&mut self) {
+        while self.outgoing.is_empty() == false && self.closed == false {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => self.closed = true,
+                Ok(written) => { self.outgoing.drain(..written); },
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {},
+                Err(_) => self.closed = true,
+            }
+        }
+    }
+
+    /// Reads all available data and returns the complete frames received
+    pub fn receive(&mut self) -> Vec<Vec<u8>> {
+        let mut buffer = [0_u8; 4096];
+        while self.closed == false {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => self.closed = true,
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {},
+                Err(_) => self.closed = true,
+            }
+        }
+
+        let mut frames = vec![];
+        while self.incoming.len() >= 4 {
+            let length = u32::from_be_bytes([self.incoming[0], self.incoming[1], self.incoming[2], self.incoming[3]]) as usize;
+            if length > MAX_FRAME_SIZE {
+                self.closed = true;
+                break;
+            }
+            if self.incoming.len() < 4 + length {
+                break;
+            }
+            frames.push(self.incoming[4..4 + length].to_vec());
+            self.incoming.drain(..4 + length);
+        }
+        frames
+    }
+}
+
+/// A client connection to a game server
+pub struct NetworkClient {
+    connection                  : Connection,
+    frames                      : Vec<Vec<u8>>,
+
+    /// The player id the server assigned to us
+    pub player_id               : usize,
+    /// The negotiated codec
+    pub codec                   : Codec,
+}
+
+impl NetworkClient {
+
+    /// Connects to the server, requests the given codec and waits for the welcome frame, gives up after CONNECT_TIMEOUT
+    pub fn connect<A: ToSocketAddrs>(addr: A, codec: Codec) -> std::io::Result<Self> {
+        Self::connect_timeout(addr, codec, CONNECT_TIMEOUT)
+    }
+
+    /// Connects to the server and waits for the welcome frame, fails with TimedOut if this takes longer than the timeout
+    pub fn connect_timeout<A: ToSocketAddrs>(addr: A, codec: Codec, timeout: Duration) -> std::io::Result<Self> {
+        let deadline = Instant::now() + timeout;
+
+        let mut stream = Err(std::io::Error::new(ErrorKind::InvalidInput, "No address to connect to"));
+        for addr in addr.to_socket_addrs()? {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                stream = Err(std::io::Error::new(ErrorKind::TimedOut, "Timed out connecting to the server"));
+                break;
+            }
+            stream = TcpStream::connect_timeout(&addr, remaining);
+            if stream.is_ok() {
+                break;
+            }
+        }
+        let mut connection = Connection::new(stream?)?;
+
+        if let Some(hello) = serde_json::to_vec(&ClientHello { codec }).ok() {
+            connection.send(&hello);
+        }
+
+        loop {
+            connection.flush();
+            let mut frames = connection.receive();
+            if frames.is_empty() == false {
+                let welcome = frames.remove(0);
+                if let Some(welcome) = serde_json::from_slice::<ServerWelcome>(&welcome).ok() {
+                    return Ok(Self {
+                        connection,
+                        frames,
+                        player_id       : welcome.player_id,
+                        codec           : welcome.codec,
+                    });
+                }
+                return Err(std::io::Error::new(ErrorKind::InvalidData, "Invalid welcome frame"));
+            }
+            if connection.closed {
+                return Err(std::io::Error::new(ErrorKind::ConnectionAborted, "Connection closed by server"));
+            }
+            if Instant::now() >= deadline {
+                return Err(std::io::Error::new(ErrorKind::TimedOut, "No welcome from the server"));
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Sends a packed (JSON) PlayerAction to the server
+    pub fn send_action(&mut self, action: String) {
+        if let Some(action) = serde_json::from_str::<PlayerAction>(&action).ok() {
+            self.send_player_action(&action);
+        }
+    }
+
+    /// Sends the PlayerAction to the server
+    pub fn send_player_action(&mut self, action: &PlayerAction) {
+        if let Some(bytes) = self.codec.encode(action) {
+            self.connection.send(&bytes);
+        }
+    }
+
+    /// Returns the next GameUpdate received from the server, if any
+    pub fn poll_update(&mut self) -> Option<GameUpdate> {
+        self.connection.flush();
+        let mut frames = self.connection.receive();
+        self.frames.append(&mut frames);
+
+        while self.frames.is_empty() == false {
+            let frame = self.frames.remove(0);
+            if let Some(update) = self.codec.decode::<GameUpdate>(&frame) {
+                return Some(update);
+            }
+        }
+        None
+    }
+
+    /// True if the server closed the connection
+    pub fn is_closed(&self) -> bool {
+        self.connection.closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn peer_which_does_not_read_is_disconnected() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let mut connection = Connection::new(stream).unwrap();
+
+        let payload = vec![0_u8; 1024 * 1024];
+        let mut sent = 0;
+        while connection.closed == false && sent < 2 * MAX_OUTGOING_SIZE {
+            connection.send(&payload);
+            sent += payload.len();
+        }
+
+        assert!(connection.closed);
+        assert!(connection.outgoing.is_empty());
+    }
+
+    #[test]
+    fn connect_times_out_without_welcome() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let start = Instant::now();
+        let rc = NetworkClient::connect_timeout(listener.local_addr().unwrap(), Codec::Binary, Duration::from_millis(100));
+        assert!(rc.err().map(|e| e.kind()) == Some(ErrorKind::TimedOut));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// A quest in the journal of the player
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct QuestData {
+    pub name            : String,
+    pub description     : String,
+    /// The description of the current stage, empty once the quest is completed
+    pub objective       : String,
+    /// The progress of the current stage, i.e. the number of killed characters
+    pub progress        : usize,
+    pub required        : usize,
+    pub completed       : bool,
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{asset::TileUsage, property::PropertySink};
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct RegionArea {
     pub name            : String,
     pub id              : usize,
@@ -14,7 +14,26 @@ pub struct RegionArea {
 
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+/// A character instance placed in the region
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct RegionInstance {
+    pub behavior        : usize,
+    pub position        : (isize, isize),
+
+    /// Overrides the name of the behavior
+    #[serde(default)]
+    pub name            : Option<String>,
+
+    /// Overrides the default values of the number variables of the behavior
+    #[serde(default)]
+    pub number_values   : Vec<(String, f64)>,
+
+    /// Overrides the faction of the behavior
+    #[serde(default)]
+    pub faction         : Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct GameRegionData {
     #[serde(with = "vectorize")]
     pub layer1          : HashMap<(isize, isize), (usize, usize, usize, TileUsage)>,
@@ -30,5 +49,9 @@ pub struct GameRegionData {
     pub max_pos         : (isize, isize),
     pub areas           : Vec<RegionArea>,
 
+    /// The character instances placed in this region
+    #[serde(default)]
+    pub instances       : Vec<RegionInstance>,
+
     pub settings        : PropertySink,
 }
\ No newline at end of file
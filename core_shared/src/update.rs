@@ -1,13 +1,15 @@
-
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{regiondata::GameRegionData, characterdata::CharacterData, asset::TileUsage, message::MessageData, light::Light};
+use crate::{regiondata::GameRegionData, characterdata::CharacterData, asset::TileUsage, message::{MessageData, DialogData}, light::Light, item::ItemData, quest::QuestData};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct GameUpdate {
 
+    /// Keyframes contain the full state. Otherwise characters, displacements, lights, loot, inventory, globals, the journal and the party only contain the changes since the previous update
+    pub keyframe                : bool,
+
     pub position                : Option<(usize, isize, isize)>,
     pub tile                    : Option<(usize, usize, usize)>,
 
@@ -20,18 +22,71 @@ pub struct GameUpdate {
     /// Current lights in the region
     pub lights                  : Vec<Light>,
 
+    /// For delta updates, true if the lights changed and the lights field holds the new lights
+    pub lights_changed          : bool,
+
     /// Tile displacements for the region
     #[serde(with = "vectorize")]
     pub displacements           : HashMap<(isize, isize), (usize, usize, usize, TileUsage)>,
 
+    /// For delta updates, the displacements which are gone
+    pub removed_displacements   : Vec<(isize, isize)>,
+
     /// Character information
     pub characters              : Vec<CharacterData>,
 
+    /// For delta updates, the ids of the characters which are gone
+    pub removed_characters      : Vec<usize>,
+
+    /// The items lying in the region
+    pub loot                    : Vec<((isize, isize), ItemData)>,
+
+    /// For delta updates, true if the loot changed and the loot field holds the new loot
+    pub loot_changed            : bool,
+
+    /// The inventory of the player
+    pub inventory               : Vec<ItemData>,
+
+    /// For delta updates, true if the inventory changed and the inventory field holds the new inventory
+    pub inventory_changed       : bool,
+
     /// Messages
     pub messages                : Vec<MessageData>,
 
     /// Audio files to play
     pub audio                   : Vec<String>,
+
+    /// The time of day in minutes since midnight
+    #[serde(default)]
+    pub time                    : usize,
+
+    /// The global variables, sorted by name
+    #[serde(default)]
+    pub globals                 : Vec<(String, f64)>,
+
+    /// For delta updates, true if the globals changed and the globals field holds the new values
+    #[serde(default)]
+    pub globals_changed         : bool,
+
+    /// The open conversation of the player, if any
+    #[serde(default)]
+    pub dialog                  : Option<DialogData>,
+
+    /// The quests of the player
+    #[serde(default)]
+    pub journal                 : Vec<QuestData>,
+
+    /// For delta updates, true if the journal changed and the journal field holds the new journal
+    #[serde(default)]
+    pub journal_changed         : bool,
+
+    /// The other members of the party of the player
+    #[serde(default)]
+    pub party                   : Vec<CharacterData>,
+
+    /// For delta updates, true if the party changed and the party field holds the new party
+    #[serde(default)]
+    pub party_changed           : bool,
 }
 
 impl GameUpdate {
@@ -39,15 +94,269 @@ impl GameUpdate {
     pub fn new() -> Self {
 
         Self {
+            keyframe            : true,
             position            : None,
             tile                : None,
             screen              : None,
             region              : None,
             lights              : vec![],
+            lights_changed      : false,
             displacements       : HashMap::new(),
+            removed_displacements: vec![],
             characters          : vec![],
+            removed_characters  : vec![],
+            loot                : vec![],
+            loot_changed        : false,
+            inventory           : vec![],
+            inventory_changed   : false,
             messages            : vec![],
             audio               : vec![],
+            time                : 0,
+            globals             : vec![],
+            globals_changed     : false,
+            dialog              : None,
+            journal             : vec![],
+            journal_changed     : false,
+            party               : vec![],
+            party_changed       : false,
         }
     }
-}
\ No newline at end of file
+
+    /// Creates a delta update containing the changes from the given previous keyframe state to this one
+    pub fn diff(&self, prev: &GameUpdate) -> GameUpdate {
+
+        let mut delta = GameUpdate::new();
+        delta.keyframe = false;
+
+        delta.position = self.position;
+        delta.tile = self.tile.clone();
+        delta.screen = self.screen.clone();
+        delta.region = self.region.clone();
+        delta.messages = self.messages.clone();
+        delta.audio = self.audio.clone();
+        delta.time = self.time;
+        delta.dialog = self.dialog.clone();
+
+        // Characters which are new or changed
+        let prev_characters : HashMap<usize, &CharacterData> = prev.characters.iter().map(|c| (c.id, c)).collect();
+        for character in &self.characters {
+            if prev_characters.get(&character.id) != Some(&character) {
+                delta.characters.push(character.clone());
+            }
+        }
+
+        let ids : HashSet<usize> = self.characters.iter().map(|c| c.id).collect();
+        for character in &prev.characters {
+            if ids.contains(&character.id) == false {
+                delta.removed_characters.push(character.id);
+            }
+        }
+
+        // Displacements
+        for (pos, value) in &self.displacements {
+            if prev.displacements.get(pos) != Some(value) {
+                delta.displacements.insert(*pos, value.clone());
+            }
+        }
+
+        for pos in prev.displacements.keys() {
+            if self.displacements.contains_key(pos) == false {
+                delta.removed_displacements.push(*pos);
+            }
+        }
+
+        // Lights
+        if self.lights != prev.lights {
+            delta.lights = self.lights.clone();
+            delta.lights_changed = true;
+        }
+
+        // Loot and inventory
+        if self.loot != prev.loot {
+            delta.loot = self.loot.clone();
+            delta.loot_changed = true;
+        }
+
+        if self.inventory != prev.inventory {
+            delta.inventory = self.inventory.clone();
+            delta.inventory_changed = true;
+        }
+
+        if self.globals != prev.globals {
+            delta.globals = self.globals.clone();
+            delta.globals_changed = true;
+        }
+
+        if self.journal != prev.journal {
+            delta.journal = self.journal.clone();
+            delta.journal_changed = true;
+        }
+
+        if self.party != prev.party {
+            delta.party = self.party.clone();
+            delta.party_changed = true;
+        }
+
+        delta
+    }
+
+    /// Applies the state of the given keyframe or delta update (position, tile, time, dialog, characters, displacements, lights, loot, inventory, globals, journal and party).
+    /// Screens, regions, messages and audio are events and not part of the state.
+    pub fn apply(&mut self, update: &GameUpdate) {
+
+        self.position = update.position;
+        self.tile = update.tile.clone();
+        self.time = update.time;
+        self.dialog = update.dialog.clone();
+
+        if update.keyframe {
+            self.characters = update.characters.clone();
+            self.displacements = update.displacements.clone();
+            self.lights = update.lights.clone();
+            self.loot = update.loot.clone();
+            self.inventory = update.inventory.clone();
+            self.globals = update.globals.clone();
+            self.journal = update.journal.clone();
+            self.party = update.party.clone();
+        } else {
+            let removed : HashSet<usize> = update.removed_characters.iter().cloned().collect();
+            self.characters.retain(|c| removed.contains(&c.id) == false);
+
+            for character in &update.characters {
+                if let Some(existing) = self.characters.iter_mut().find(|c| c.id == character.id) {
+                    *existing = character.clone();
+                } else {
+                    self.characters.push(character.clone());
+                }
+            }
+
+            for pos in &update.removed_displacements {
+                self.displacements.remove(pos);
+            }
+            for (pos, value) in &update.displacements {
+                self.displacements.insert(*pos, value.clone());
+            }
+
+            if update.lights_changed {
+                self.lights = update.lights.clone();
+            }
+
+            if update.loot_changed {
+                self.loot = update.loot.clone();
+            }
+
+            if update.inventory_changed {
+                self.inventory = update.inventory.clone();
+            }
+
+            if update.globals_changed {
+                self.globals = update.globals.clone();
+            }
+
+            if update.journal_changed {
+                self.journal = update.journal.clone();
+            }
+
+            if update.party_changed {
+                self.party = update.party.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::light::LightType;
+
+    fn character(id: usize, x: isize) -> CharacterData {
+        CharacterData { name: format!("Character {}", id), id, index: id, position: (1, x, 0), tile: (0, 0, id) }
+    }
+
+    fn item(id: usize) -> ItemData {
+        ItemData { id, item_id: 7, name: "Sword".to_string(), tile: Some((0, 1, 2)) }
+    }
+
+    fn quest(progress: usize) -> QuestData {
+        QuestData { name: "Orcs".to_string(), description: "Kill the orcs".to_string(), objective: "Kill 3 orcs".to_string(), progress, required: 3, completed: false }
+    }
+
+    /// The state before the delta
+    fn prev() -> GameUpdate {
+        let mut update = GameUpdate::new();
+        update.position = Some((1, 2, 3));
+        update.tile = Some((0, 0, 1));
+        update.time = 100;
+        update.characters = vec![character(1, 0), character(2, 0), character(3, 0)];
+        update.displacements.insert((0, 0), (0, 0, 1, TileUsage::Environment));
+        update.displacements.insert((1, 0), (0, 0, 2, TileUsage::Environment));
+        update.lights = vec![Light::new(LightType::PointLight, (0, 0), 3)];
+        update.loot = vec![((2, 2), item(1))];
+        update.globals = vec![("gold".to_string(), 1.0)];
+        update.journal = vec![quest(0)];
+        update
+    }
+
+    /// The state after the delta
+    fn next() -> GameUpdate {
+        let mut update = GameUpdate::new();
+        update.position = Some((1, 3, 3));
+        update.tile = Some((0, 0, 2));
+        update.time = 101;
+        update.characters = vec![character(1, 0), character(3, 5), character(4, 1)];
+        update.displacements.insert((0, 0), (0, 0, 1, TileUsage::Environment));
+        update.displacements.insert((2, 0), (0, 0, 3, TileUsage::EnvBlocking));
+        update.lights = vec![Light::new(LightType::PointLight, (1, 0), 3)];
+        update.inventory = vec![item(1)];
+        update.globals = vec![("gold".to_string(), 2.0)];
+        update.journal = vec![quest(1)];
+        update.party = vec![character(5, 1)];
+        update
+    }
+
+    #[test]
+    fn delta_contains_the_changes() {
+        let delta = next().diff(&prev());
+
+        assert!(delta.keyframe == false);
+        assert!(delta.characters == vec![character(3, 5), character(4, 1)]);
+        assert_eq!(delta.removed_characters, vec![2]);
+        assert_eq!(delta.displacements.len(), 1);
+        assert!(delta.displacements.contains_key(&(2, 0)));
+        assert_eq!(delta.removed_displacements, vec![(1, 0)]);
+        assert!(delta.lights_changed && delta.loot_changed && delta.inventory_changed);
+        assert!(delta.globals_changed && delta.journal_changed && delta.party_changed);
+    }
+
+    #[test]
+    fn applying_the_delta_restores_the_state() {
+        let mut state = prev();
+        state.apply(&next().diff(&prev()));
+        assert!(state == next());
+
+        // Without changes the delta is empty and keeps the state
+        let delta = next().diff(&next());
+        assert!(delta.characters.is_empty() && delta.removed_characters.is_empty());
+        assert!(delta.displacements.is_empty() && delta.removed_displacements.is_empty());
+        assert!(delta.lights_changed == false && delta.loot_changed == false && delta.inventory_changed == false);
+        assert!(delta.globals_changed == false && delta.journal_changed == false && delta.party_changed == false);
+
+        state.apply(&delta);
+        assert!(state == next());
+    }
+
+    #[test]
+    fn keyframe_resets_the_state() {
+        let mut state = prev();
+        state.apply(&next().diff(&prev()));
+
+        // A keyframe replaces everything, including entries a delta would have kept
+        let mut keyframe = GameUpdate::new();
+        keyframe.characters = vec![character(9, 0)];
+        keyframe.displacements.insert((5, 5), (0, 0, 4, TileUsage::Water));
+        state.apply(&keyframe);
+
+        assert!(state == keyframe);
+        assert!(state.lights.is_empty() && state.inventory.is_empty() && state.journal.is_empty() && state.party.is_empty());
+    }
+}
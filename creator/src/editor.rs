@@ -9,6 +9,7 @@ use crate::editor::items_overview_options::ItemsOverviewOptions;
 use crate::editor::regionwidget::RegionWidget;
 use crate::editor::region_overview_options::RegionOverviewOptions;
 use crate::editor::log::LogWidget;
+use crate::editor::quests::QuestsWidget;
 use crate::editor::gameoptions::GameOptions;
 use crate::widget:: {ScreenWidget, Widget, WidgetState, WidgetKey};
 use crate::atom:: { AtomWidget, AtomWidgetType, AtomData };
@@ -40,6 +41,7 @@ mod statusbar;
 pub mod dialog;
 pub mod dialog_position;
 mod log;
+mod quests;
 mod gameoptions;
 pub mod traits;
 pub mod codeeditorwidget;
@@ -87,6 +89,7 @@ pub struct Editor<'a> {
     controlbar                      : ControlBar,
     toolbar                         : ToolBar,
     log                             : LogWidget,
+    quests                          : QuestsWidget,
     code_editor                     : CodeEditorWidget,
 
     pub content                      : Vec<(Option<Box<dyn EditorOptions>>, Option<Box<dyn EditorContent>>)>,
@@ -129,6 +132,7 @@ impl ScreenWidget for Editor<'_> {
         let dialog_position = DialogPositionWidget::new(asset, &context);
 
         let log = LogWidget::new(&context);
+        let quests = QuestsWidget::new(&context);
         let mut status_bar = StatusBar::new();
 
         let code_editor =  CodeEditorWidget::new(vec!(), (0, context.toolbar_height, width, height - context.toolbar_height), asset, &context);
@@ -159,6 +163,7 @@ impl ScreenWidget for Editor<'_> {
             controlbar,
             toolbar,
             log,
+            quests,
             code_editor,
 
             content                 : vec![],
@@ -389,6 +394,10 @@ impl ScreenWidget for Editor<'_> {
                 self.content[EditorState::SystemsDetail as usize].1.as_mut().unwrap().set_behavior_id(self.context.data.systems_ids[self.context.curr_systems_index] , &mut self.context);
             } else
             if state == EditorState::ItemsDetail {
+                self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().set_mode_and_rect( GraphMode::Detail, (self.left_width, self.rect.1 + self.context.toolbar_height, self.rect.2 - self.left_width, self.rect.3 - self.context.toolbar_height), &self.context);
+
+                self.context.curr_graph_type = BehaviorType::Items;
+                self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().set_behavior_id(self.context.data.items_ids[self.context.curr_items_index] , &mut self.context);
             }
         }
 
@@ -428,6 +437,12 @@ impl ScreenWidget for Editor<'_> {
             self.context.draw2d.blend_slice_safe(frame, &self.log.buffer[..], &self.log.rect, self.context.width, &self.content[EditorState::BehaviorDetail as usize].1.as_mut().unwrap().get_rect());
         }
 
+        // Quest states of the players
+        if self.state == EditorState::GameDetail && self.context.is_running {
+            self.quests.draw(frame, anim_counter, asset, &mut self.context);
+            self.context.draw2d.blend_slice_safe(frame, &self.quests.buffer[..], &self.quests.rect, self.context.width, &self.content[EditorState::GameDetail as usize].1.as_mut().unwrap().get_rect());
+        }
+
         // Content: Code Editor ?
         if self.context.code_editor_is_active {
 
@@ -454,6 +469,44 @@ impl ScreenWidget for Editor<'_> {
                     }
                 }
 
+                // Variables of a placed character ?
+                if self.state == EditorState::RegionDetail && self.context.code_editor_node_behavior_id.0 == 130001 {
+
+                    let mut sink = PropertySink::new();
+                    if sink.load_from_string(self.context.code_editor_value.clone()) {
+
+                        self.context.code_editor_error = None;
+
+                        let id = self.content[self.state as usize].1.as_mut().unwrap().get_region_id();
+                        if let Some(region) = self.context.data.regions.get_mut(&id) {
+                            if let Some(index) = self.context.curr_region_instance_index {
+                                if let Some(instance) = region.data.instances.get_mut(index) {
+                                    instance.name = None;
+                                    instance.number_values = vec![];
+                                    for property in &sink.properties {
+                                        if property.name == "name" {
+                                            if let Some(name) = property.as_string() {
+                                                if name.is_empty() == false {
+                                                    instance.name = Some(name);
+                                                }
+                                            }
+                                        } else
+                                        if let Some(value) = property.as_float() {
+                                            instance.number_values.push((property.name.clone(), value as f64));
+                                        } else
+                                        if let Some(value) = property.as_int() {
+                                            instance.number_values.push((property.name.clone(), value as f64));
+                                        }
+                                    }
+                                    region.save_data();
+                                }
+                            }
+                        }
+                    } else {
+                        self.context.code_editor_error = Some((sink.error.clone().unwrap().1, Some(sink.error.unwrap().0)));
+                    }
+                }
+
                 self.context.code_editor_node_behavior_value.4 = self.context.code_editor_value.clone();
                 self.context.dialog_node_behavior_value = self.context.code_editor_node_behavior_value.clone();
                 self.context.dialog_node_behavior_id = self.context.code_editor_node_behavior_id.clone();
@@ -636,6 +689,13 @@ impl ScreenWidget for Editor<'_> {
                     self.content[EditorState::SystemsDetail as usize].1.as_mut().unwrap().update_from_dialog(&mut self.context);
                 }
             } else
+            if self.state == EditorState::ItemsDetail {
+                if self.context.dialog_entry == DialogEntry::NodeTile {
+                    self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().set_node_atom_data(self.context.dialog_node_behavior_id.clone(), self.context.dialog_node_behavior_value.clone(), &mut self.context);
+                } else {
+                    self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().update_from_dialog(&mut self.context);
+                }
+            } else
             if self.state == EditorState::BehaviorOverview {
                 if self.context.dialog_entry == DialogEntry::NewName && self.context.dialog_accepted == true {
                     //println!("dialog ended {} {}", self.context.dialog_new_name, self.context.dialog_new_name_type);
@@ -701,6 +761,37 @@ impl ScreenWidget for Editor<'_> {
                     self.content[EditorState::SystemsOverview as usize].1.as_mut().unwrap().update_from_dialog(&mut self.context);
                 }
             } else
+            if self.state == EditorState::ItemsOverview {
+                if self.context.dialog_entry == DialogEntry::NewName && self.context.dialog_accepted == true {
+                    self.context.data.create_item_behavior(self.context.dialog_new_name.clone(), 0);
+
+                    let mut node = NodeWidget::new(vec![self.context.dialog_new_name.clone()],
+                    NodeUserData { position: (100, 50 + 150 * self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().get_nodes().unwrap().len() as isize) });
+
+                    let node_menu_atom = crate::atom::AtomWidget::new(vec!["Rename".to_string(), "Delete".to_string()], crate::atom::AtomWidgetType::NodeMenu, crate::atom::AtomData::new_as_int("menu".to_string(), 0));
+                    node.menu = Some(node_menu_atom);
+
+                    self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().get_nodes().unwrap().push(node);
+                    self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().set_dirty();
+                    self.toolbar.widgets[0].text = self.context.data.items_names.clone();
+                    self.toolbar.widgets[0].dirty = true;
+                } else {
+                    if self.context.dialog_entry == DialogEntry::NodeName {
+                        if self.context.dialog_accepted == true {
+                            if let Some(item) = self.context.data.items.get_mut(&self.context.data.items_ids[self.context.curr_items_index]) {
+                                self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().get_nodes().unwrap()[self.context.curr_items_index].text[0] = self.context.dialog_node_behavior_value.4.clone();
+                                self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().get_nodes().unwrap()[self.context.curr_items_index].dirty = true;
+                                self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().set_dirty();
+                                item.rename(self.context.dialog_node_behavior_value.4.clone());
+                                self.context.data.items_names[self.context.curr_items_index] = self.context.dialog_node_behavior_value.4.clone();
+                                self.toolbar.widgets[0].text = self.context.data.items_names.clone();
+                                self.toolbar.widgets[0].dirty = true;
+                            }
+                        }
+                    }
+                    self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().update_from_dialog(&mut self.context);
+                }
+            } else
             if self.state == EditorState::GameDetail {
                 self.content[EditorState::GameDetail as usize].1.as_mut().unwrap().update_from_dialog(&mut self.context);
             }
@@ -914,7 +1005,9 @@ impl ScreenWidget for Editor<'_> {
                     self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().set_mode_and_rect( GraphMode::Detail, (self.left_width, self.rect.1 + self.context.toolbar_height, self.rect.2 - self.left_width, self.rect.3 - self.context.toolbar_height), &self.context);
                     self.state = EditorState::ItemsDetail;
                     self.context.curr_graph_type = BehaviorType::Items;
-                    //self.node_graph_items_details.set_behavior_id(self.context.data.items_ids[self.context.curr_items_index], &mut self.context);
+                    if self.context.data.items_ids.is_empty() == false {
+                        self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().set_behavior_id(self.context.data.items_ids[self.context.curr_items_index] , &mut self.context);
+                    }
                 }
 
                 for i in 1..5 {
@@ -1037,6 +1130,11 @@ impl ScreenWidget for Editor<'_> {
                     self.content[EditorState::SystemsOverview as usize].1.as_mut().unwrap().changed_selection(self.context.curr_systems_index, self.toolbar.widgets[0].curr_index);
                     self.context.curr_systems_index = self.toolbar.widgets[0].curr_index;
                     self.content[EditorState::SystemsDetail as usize].1.as_mut().unwrap().set_behavior_id(self.context.data.systems_ids[self.context.curr_systems_index] , &mut self.context);
+                } else
+                if self.state == EditorState::ItemsOverview || self.state == EditorState::ItemsDetail {
+                    self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().changed_selection(self.context.curr_items_index, self.toolbar.widgets[0].curr_index);
+                    self.context.curr_items_index = self.toolbar.widgets[0].curr_index;
+                    self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().set_behavior_id(self.context.data.items_ids[self.context.curr_items_index] , &mut self.context);
                 }
                 self.toolbar.widgets[0].new_selection = None;
             }
@@ -1164,6 +1262,36 @@ impl ScreenWidget for Editor<'_> {
                      self.content[EditorState::SystemsDetail as usize].1.as_mut().unwrap().add_node_of_name(drag_context.text.clone(), position, &mut self.context);
                 }
             } else
+            if self.state == EditorState::ItemsOverview {
+                let rect = self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().get_rect();
+                let offset = self.content[EditorState::ItemsOverview as usize].1.as_mut().unwrap().get_offset();
+                if self.context.contains_pos_for(pos, rect) {
+
+                    let mut position = (pos.0 as isize, pos.1 as isize);
+                    position.0 -= rect.0 as isize + offset.0 + drag_context.offset.0;
+                    position.1 -= rect.1 as isize + offset.1 + drag_context.offset.1;
+
+                    self.context.dialog_state = DialogState::Opening;
+                    self.context.dialog_height = 0;
+                    self.context.target_fps = 60;
+                    self.context.dialog_entry = DialogEntry::NewName;
+                    self.context.dialog_new_name = "New Item".to_string();
+                    self.context.dialog_new_name_type = format!("NewBehavior_{}", drag_context.text);
+                    self.context.dialog_new_node_position = position;
+                }
+            } else
+            if self.state == EditorState::ItemsDetail {
+                let rect = self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().get_rect();
+                let offset = self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().get_offset();
+                if self.context.contains_pos_for(pos, rect) {
+
+                    let mut position = (pos.0 as isize, pos.1 as isize);
+                    position.0 -= rect.0 as isize + offset.0 + drag_context.offset.0;
+                    position.1 -= rect.1 as isize + offset.1 + drag_context.offset.1;
+
+                     self.content[EditorState::ItemsDetail as usize].1.as_mut().unwrap().add_node_of_name(drag_context.text.clone(), position, &mut self.context);
+                }
+            } else
             if self.state == EditorState::GameDetail {
                 let rect = self.content[EditorState::GameDetail as usize].1.as_mut().unwrap().get_rect();
                 let offset = self.content[EditorState::GameDetail as usize].1.as_mut().unwrap().get_offset();
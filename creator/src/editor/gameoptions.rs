@@ -27,9 +27,9 @@ impl EditorOptions for GameOptions {
     AtomData::new_as_int("NodeList".to_string(), 0));
         node_list.drag_enabled = true;
 
-        node_list.add_group_list(context.color_green, context.color_light_green, vec!["Behavior Tree".to_string(), "Expression".to_string(), "Script".to_string(), "Linear".to_string(), "Sequence".to_string()]);
+        node_list.add_group_list(context.color_green, context.color_light_green, vec!["Behavior Tree".to_string(), "Expression".to_string(), "Script".to_string(), "Linear".to_string(), "Sequence".to_string(), "Selector".to_string(), "Inverter".to_string(), "Repeat".to_string(), "Parallel".to_string(), "Cooldown".to_string(), "Random".to_string()]);
 
-        node_list.add_group_list(context.color_orange, context.color_light_orange, vec!["Number".to_string()]);
+        node_list.add_group_list(context.color_orange, context.color_light_orange, vec!["Number".to_string(), "Quest".to_string()]);
 
         node_list.add_group_list(context.color_blue, context.color_light_blue, vec![ "Screen".to_string(), "Widget".to_string(), "Settings".to_string() ]);
 
@@ -27,11 +27,11 @@ impl EditorOptions for ItemsOptions {
     AtomData::new_as_int("NodeList".to_string(), 0));
         node_list.drag_enabled = true;
 
-        node_list.add_group_list(context.color_green, context.color_light_green, vec!["Behavior Tree".to_string(), "Expression".to_string(), "Script".to_string(), "Linear".to_string(), "Sequence".to_string()]);
+        node_list.add_group_list(context.color_green, context.color_light_green, vec!["Behavior Tree".to_string(), "Expression".to_string(), "Script".to_string(), "Linear".to_string(), "Sequence".to_string(), "Selector".to_string(), "Inverter".to_string(), "Repeat".to_string(), "Parallel".to_string(), "Cooldown".to_string(), "Random".to_string()]);
 
         // node_list.add_group_list(context.color_orange, context.color_light_orange, vec!["Number".to_string(),/* "Position".to_string()*/ ]);
 
-        // node_list.add_group_list(context.color_blue, context.color_light_blue, vec![ "Close In".to_string(), "Lookout".to_string(), "Pathfinder".to_string(), "Call Behavior".to_string(), "Call System".to_string(), "Lock Tree".to_string(), "Unlock".to_string(), "Set State".to_string(), "Message".to_string() ]);
+        node_list.add_group_list(context.color_blue, context.color_light_blue, vec![ "Call System".to_string(), "Set State".to_string(), "Message".to_string() ]);
 
         node_list.set_rect(rect, asset, context);
         widgets.push(node_list);
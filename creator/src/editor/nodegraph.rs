@@ -1179,6 +1179,31 @@ impl EditorContent for NodeGraph  {
                 "Set State" => BehaviorNodeType::SetState,
                 "Linear" => BehaviorNodeType::Linear,
                 "Move" => BehaviorNodeType::Move,
+                "Take" => BehaviorNodeType::Take,
+                "Drop" => BehaviorNodeType::Drop,
+                "Use Item" => BehaviorNodeType::UseItem,
+                "Time of Day" => BehaviorNodeType::TimeOfDay,
+                "Schedule" => BehaviorNodeType::Schedule,
+                "Selector" => BehaviorNodeType::Selector,
+                "Inverter" => BehaviorNodeType::Inverter,
+                "Repeat" => BehaviorNodeType::Repeat,
+                "Parallel" => BehaviorNodeType::Parallel,
+                "Cooldown" => BehaviorNodeType::Cooldown,
+                "Random" => BehaviorNodeType::Random,
+                "Emit Event" => BehaviorNodeType::EmitEvent,
+                "Talk" => BehaviorNodeType::Talk,
+                "Dialog" => BehaviorNodeType::Dialog,
+                "Quest" => BehaviorNodeType::Quest,
+                "Start Quest" => BehaviorNodeType::StartQuest,
+                "Advance Quest" => BehaviorNodeType::AdvanceQuest,
+                "Complete Quest" => BehaviorNodeType::CompleteQuest,
+                "Adjust Reputation" => BehaviorNodeType::AdjustReputation,
+                "Recruit" => BehaviorNodeType::Recruit,
+                "Dismiss" => BehaviorNodeType::Dismiss,
+                "Follow" => BehaviorNodeType::Follow,
+                "Wander" => BehaviorNodeType::Wander,
+                "Flee" => BehaviorNodeType::Flee,
+                "Patrol" => BehaviorNodeType::Patrol,
 
                 "Always" => BehaviorNodeType::Always,
                 "Enter Area" => BehaviorNodeType::EnterArea,
@@ -1273,6 +1298,37 @@ impl EditorContent for NodeGraph  {
                 tile_atom.behavior_id = Some(id.clone());
                 tile_atom.atom_data.data = context.data.get_behavior_id_value(id, (-1.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
                 node_widget.widgets.push(tile_atom);
+
+                // Faction
+                let mut faction_atom = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+                AtomData::new_as_int("faction".to_string(), 0));
+                faction_atom.atom_data.text = "Faction".to_string();
+                let id = (behavior_data.id, node_data.id, "faction".to_string());
+                faction_atom.behavior_id = Some(id.clone());
+                faction_atom.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+                node_widget.widgets.push(faction_atom);
+            } else
+            if self.graph_type == BehaviorType::Items {
+                node_widget.is_corner_node = true;
+                node_widget.color = context.color_black.clone();
+
+                // Position, items with a position are placed in the region on startup
+                let mut position_atom = AtomWidget::new(vec![], AtomWidgetType::NodePositionButton,
+                AtomData::new_as_int("position".to_string(), 0));
+                position_atom.atom_data.text = "position".to_string();
+                let id = (behavior_data.id, node_data.id, "position".to_string());
+                position_atom.behavior_id = Some(id.clone());
+                position_atom.atom_data.data = context.data.get_behavior_id_value(id, (-1.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+                node_widget.widgets.push(position_atom);
+
+                // Item Tile
+                let mut tile_atom = AtomWidget::new(vec![], AtomWidgetType::NodeIconTileButton,
+                    AtomData::new_as_int("tile".to_string(), 0));
+                tile_atom.atom_data.text = "tile".to_string();
+                let id = (behavior_data.id, node_data.id, "tile".to_string());
+                tile_atom.behavior_id = Some(id.clone());
+                tile_atom.atom_data.data = context.data.get_behavior_id_value(id, (-1.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+                node_widget.widgets.push(tile_atom);
             } else
             if self.graph_type == BehaviorType::GameLogic {
                 node_widget.is_corner_node = true;
@@ -1285,6 +1341,41 @@ impl EditorContent for NodeGraph  {
                 startup_atom.behavior_id = Some(id.clone());
                 startup_atom.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "Game".to_string()), self.graph_type);
                 node_widget.widgets.push(startup_atom);
+
+                // Game clock
+                let mut ticks_atom = AtomWidget::new(vec!["Ticks per Minute".to_string()], AtomWidgetType::NodeNumberButton,
+                AtomData::new_as_int("ticks_per_minute".to_string(), 0));
+                ticks_atom.atom_data.text = "Ticks per Minute".to_string();
+                let id = (behavior_data.id, node_data.id, "ticks_per_minute".to_string());
+                ticks_atom.behavior_id = Some(id.clone());
+                ticks_atom.atom_data.data = context.data.get_behavior_id_value(id, (4.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+                node_widget.widgets.push(ticks_atom);
+
+                let mut start_atom = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+                AtomData::new_as_int("start_time".to_string(), 0));
+                start_atom.atom_data.text = "Start Time".to_string();
+                let id = (behavior_data.id, node_data.id, "start_time".to_string());
+                start_atom.behavior_id = Some(id.clone());
+                start_atom.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "08:00".to_string()), self.graph_type);
+                node_widget.widgets.push(start_atom);
+
+                // Reputations between the factions
+                let mut factions_atom = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+                AtomData::new_as_int("factions".to_string(), 0));
+                factions_atom.atom_data.text = "Factions".to_string();
+                let id = (behavior_data.id, node_data.id, "factions".to_string());
+                factions_atom.behavior_id = Some(id.clone());
+                factions_atom.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "// Faction, Faction, Reputation from -100 (hostile) to 100 (allied)\n".to_string()), self.graph_type);
+                node_widget.widgets.push(factions_atom);
+
+                // Globals which screen scripts may change
+                let mut screen_globals_atom = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+                AtomData::new_as_int("screen_globals".to_string(), 0));
+                screen_globals_atom.atom_data.text = "Screen Globals".to_string();
+                let id = (behavior_data.id, node_data.id, "screen_globals".to_string());
+                screen_globals_atom.behavior_id = Some(id.clone());
+                screen_globals_atom.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+                node_widget.widgets.push(screen_globals_atom);
             }
             return;
         }
@@ -1307,7 +1398,7 @@ impl EditorContent for NodeGraph  {
         // Behavior
 
         if node_data.behavior_type == BehaviorNodeType::BehaviorTree {
-            let mut atom1 = AtomWidget::new(vec!["Always".to_string(), "On Startup".to_string(), "On Target".to_string()], AtomWidgetType::NodeMenuButton,
+            let mut atom1 = AtomWidget::new(vec!["Always".to_string(), "On Startup".to_string(), "On Target".to_string(), "On Event".to_string()], AtomWidgetType::NodeMenuButton,
             AtomData::new_as_int("execute".to_string(), 0));
             atom1.atom_data.text = "Execute".to_string();
             let id = (behavior_data.id, node_data.id, "execute".to_string());
@@ -1348,6 +1439,66 @@ impl EditorContent for NodeGraph  {
             node_widget.node_connector.insert(BehaviorNodeConnector::Bottom3, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Bottom4, NodeConnector { rect: (0,0,0,0) } );
         } else
+        if node_data.behavior_type == BehaviorNodeType::Selector || node_data.behavior_type == BehaviorNodeType::Parallel || node_data.behavior_type == BehaviorNodeType::Random {
+            if node_data.behavior_type == BehaviorNodeType::Parallel {
+                let mut atom1 = AtomWidget::new(vec!["All Succeed".to_string(), "One Succeeds".to_string()], AtomWidgetType::NodeMenuButton,
+                AtomData::new_as_int("policy".to_string(), 0));
+                atom1.atom_data.text = "Policy".to_string();
+                let id = (behavior_data.id, node_data.id, "policy".to_string());
+                atom1.behavior_id = Some(id.clone());
+                atom1.curr_index = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type).0 as usize;
+                node_widget.widgets.push(atom1);
+            } else
+            if node_data.behavior_type == BehaviorNodeType::Random {
+                let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+                AtomData::new_as_int("weights".to_string(), 0));
+                atom1.atom_data.text = "Weights".to_string();
+                let id = (behavior_data.id, node_data.id, "weights".to_string());
+                atom1.behavior_id = Some(id.clone());
+                atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "1, 1, 1, 1, 1".to_string()), self.graph_type);
+                node_widget.widgets.push(atom1);
+            }
+
+            node_widget.color = context.color_green.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom1, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom2, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom3, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom4, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::Inverter || node_data.behavior_type == BehaviorNodeType::Repeat || node_data.behavior_type == BehaviorNodeType::Cooldown {
+            if node_data.behavior_type == BehaviorNodeType::Repeat {
+                let mut atom1 = AtomWidget::new(vec!["Count".to_string(), "Until Fail".to_string()], AtomWidgetType::NodeMenuButton,
+                AtomData::new_as_int("mode".to_string(), 0));
+                atom1.atom_data.text = "Mode".to_string();
+                let id = (behavior_data.id, node_data.id, "mode".to_string());
+                atom1.behavior_id = Some(id.clone());
+                atom1.curr_index = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type).0 as usize;
+                node_widget.widgets.push(atom1);
+
+                let mut atom2 = AtomWidget::new(vec!["Count".to_string()], AtomWidgetType::NodeExpressionValueButton,
+                AtomData::new_as_int("count".to_string(), 0));
+                atom2.atom_data.text = "Count".to_string();
+                let id = (behavior_data.id, node_data.id, "count".to_string());
+                atom2.behavior_id = Some(id.clone());
+                atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "3".to_string()), self.graph_type);
+                node_widget.widgets.push(atom2);
+            } else
+            if node_data.behavior_type == BehaviorNodeType::Cooldown {
+                let mut atom1 = AtomWidget::new(vec!["Ticks".to_string()], AtomWidgetType::NodeExpressionValueButton,
+                AtomData::new_as_int("ticks".to_string(), 0));
+                atom1.atom_data.text = "Ticks".to_string();
+                let id = (behavior_data.id, node_data.id, "ticks".to_string());
+                atom1.behavior_id = Some(id.clone());
+                atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "10".to_string()), self.graph_type);
+                node_widget.widgets.push(atom1);
+            }
+
+            node_widget.color = context.color_green.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom, NodeConnector { rect: (0,0,0,0) } );
+        } else
         if node_data.behavior_type == BehaviorNodeType::Expression {
             let mut atom1 = AtomWidget::new(vec!["Expression".to_string()], AtomWidgetType::NodeExpressionButton,
             AtomData::new_as_int("expression".to_string(), 0));
@@ -1357,11 +1508,46 @@ impl EditorContent for NodeGraph  {
             atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "false".to_string()), self.graph_type);
             node_widget.widgets.push(atom1);
 
-            node_widget.color = context.color_green.clone();
-            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
-            node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
-            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
-            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+            if self.graph_type == BehaviorType::Regions {
+                node_widget.color = context.color_blue.clone();
+                node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            } else {
+                node_widget.color = context.color_green.clone();
+                node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+            }
+        } else
+        if node_data.behavior_type == BehaviorNodeType::TimeOfDay {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("from".to_string(), 0));
+            atom1.atom_data.text = "From".to_string();
+            let id = (behavior_data.id, node_data.id, "from".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "06:00".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("to".to_string(), 0));
+            atom2.atom_data.text = "To".to_string();
+            let id = (behavior_data.id, node_data.id, "to".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "18:00".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
+            if self.graph_type == BehaviorType::Regions {
+                node_widget.color = context.color_blue.clone();
+                node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            } else {
+                node_widget.color = context.color_green.clone();
+                node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+            }
         } else
         if node_data.behavior_type == BehaviorNodeType::Script {
             let mut atom1 = AtomWidget::new(vec!["Script".to_string()], AtomWidgetType::NodeScriptButton,
@@ -1372,10 +1558,16 @@ impl EditorContent for NodeGraph  {
             atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
             node_widget.widgets.push(atom1);
 
-            node_widget.color = context.color_green.clone();
-            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
-            node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
-            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom, NodeConnector { rect: (0,0,0,0) } );
+            if self.graph_type == BehaviorType::Regions {
+                node_widget.color = context.color_blue.clone();
+                node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            } else {
+                node_widget.color = context.color_green.clone();
+                node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Bottom, NodeConnector { rect: (0,0,0,0) } );
+            }
         } else
         if node_data.behavior_type == BehaviorNodeType::VariableNumber {
 
@@ -1391,7 +1583,11 @@ impl EditorContent for NodeGraph  {
             node_widget.is_variable_node = true;
         } else
         if node_data.behavior_type == BehaviorNodeType::Message || node_data.behavior_type == BehaviorNodeType::MessageArea {
-            let mut atom1 = AtomWidget::new(vec!["Status".to_string(), "Say".to_string(), "Yell".to_string(), "Private".to_string(), "Debug".to_string()], AtomWidgetType::NodeMenuButton,
+            let mut types = vec!["Status".to_string(), "Say".to_string(), "Yell".to_string(), "Private".to_string(), "Debug".to_string()];
+            if node_data.behavior_type == BehaviorNodeType::Message {
+                types.push("Party".to_string());
+            }
+            let mut atom1 = AtomWidget::new(types, AtomWidgetType::NodeMenuButton,
             AtomData::new_as_int("type".to_string(), 0));
             atom1.atom_data.text = "Type".to_string();
             let id = (behavior_data.id, node_data.id, "type".to_string());
@@ -1412,6 +1608,41 @@ impl EditorContent for NodeGraph  {
             node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Bottom, NodeConnector { rect: (0,0,0,0) } );
         } else
+        if node_data.behavior_type == BehaviorNodeType::EmitEvent {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("event".to_string(), 0));
+            atom1.atom_data.text = "Event".to_string();
+            let id = (behavior_data.id, node_data.id, "event".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "alarm".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec!["Target".to_string(), "Area".to_string(), "Region".to_string()], AtomWidgetType::NodeMenuButton,
+            AtomData::new_as_int("to".to_string(), 0));
+            atom2.atom_data.text = "To".to_string();
+            let id = (behavior_data.id, node_data.id, "to".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.curr_index = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type).0 as usize;
+            node_widget.widgets.push(atom2);
+
+            let mut atom3 = AtomWidget::new(vec!["Payload".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("payload".to_string(), 0));
+            atom3.atom_data.text = "Payload".to_string();
+            let id = (behavior_data.id, node_data.id, "payload".to_string());
+            atom3.behavior_id = Some(id.clone());
+            atom3.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "0".to_string()), self.graph_type);
+            node_widget.widgets.push(atom3);
+
+            node_widget.color = context.color_blue.clone();
+            if self.graph_type == BehaviorType::Regions {
+                node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            } else {
+                node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+                node_widget.node_connector.insert(BehaviorNodeConnector::Bottom, NodeConnector { rect: (0,0,0,0) } );
+            }
+        } else
         if node_data.behavior_type == BehaviorNodeType::Pathfinder {
             let mut atom1 = AtomWidget::new(vec!["Destination".to_string()], AtomWidgetType::NodePositionButton,
             AtomData::new_as_int("destination".to_string(), 0));
@@ -1435,6 +1666,45 @@ impl EditorContent for NodeGraph  {
             node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
         } else
+        if node_data.behavior_type == BehaviorNodeType::Schedule {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("schedule".to_string(), 0));
+            atom1.atom_data.text = "Schedule".to_string();
+            let id = (behavior_data.id, node_data.id, "schedule".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "// From - To, Area or X Y, Tree\n08:00 - 18:00, Shop, Open Shop".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec!["Speed".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("speed".to_string(), 0));
+            atom2.atom_data.text = "Speed".to_string();
+            let id = (behavior_data.id, node_data.id, "speed".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "8".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
+            let mut atom3 = AtomWidget::new(vec!["Day Length".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("day_length".to_string(), 0));
+            atom3.atom_data.text = "Day Length".to_string();
+            let id = (behavior_data.id, node_data.id, "day_length".to_string());
+            atom3.behavior_id = Some(id.clone());
+            atom3.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "24".to_string()), self.graph_type);
+            node_widget.widgets.push(atom3);
+
+            let mut atom4 = AtomWidget::new(vec!["Offset".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("offset".to_string(), 0));
+            atom4.atom_data.text = "Offset".to_string();
+            let id = (behavior_data.id, node_data.id, "offset".to_string());
+            atom4.behavior_id = Some(id.clone());
+            atom4.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "0".to_string()), self.graph_type);
+            node_widget.widgets.push(atom4);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
         if node_data.behavior_type == BehaviorNodeType::Lookout {
             let mut atom1 = AtomWidget::new(vec!["Expression".to_string()], AtomWidgetType::NodeExpressionButton,
             AtomData::new_as_int("expression".to_string(), 0));
@@ -1452,6 +1722,23 @@ impl EditorContent for NodeGraph  {
             atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "7".to_string()), self.graph_type);
             node_widget.widgets.push(atom2);
 
+            // View cone in degrees around the facing direction
+            let mut atom3 = AtomWidget::new(vec!["Field of View".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("field_of_view".to_string(), 0));
+            atom3.atom_data.text = "Field of View".to_string();
+            let id = (behavior_data.id, node_data.id, "field_of_view".to_string());
+            atom3.behavior_id = Some(id.clone());
+            atom3.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "360".to_string()), self.graph_type);
+            node_widget.widgets.push(atom3);
+
+            let mut atom4 = AtomWidget::new(vec!["Any".to_string(), "Hostile".to_string(), "Not Hostile".to_string()], AtomWidgetType::NodeMenuButton,
+            AtomData::new_as_int("faction".to_string(), 0));
+            atom4.atom_data.text = "Faction".to_string();
+            let id = (behavior_data.id, node_data.id, "faction".to_string());
+            atom4.behavior_id = Some(id.clone());
+            atom4.curr_index = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type).0 as usize;
+            node_widget.widgets.push(atom4);
+
             node_widget.color = context.color_blue.clone();
             node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
@@ -1474,12 +1761,164 @@ impl EditorContent for NodeGraph  {
             atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "8".to_string()), self.graph_type);
             node_widget.widgets.push(atom2);
 
+            let mut atom3 = AtomWidget::new(vec!["Any".to_string(), "Hostile".to_string(), "Not Hostile".to_string()], AtomWidgetType::NodeMenuButton,
+            AtomData::new_as_int("faction".to_string(), 0));
+            atom3.atom_data.text = "Faction".to_string();
+            let id = (behavior_data.id, node_data.id, "faction".to_string());
+            atom3.behavior_id = Some(id.clone());
+            atom3.curr_index = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type).0 as usize;
+            node_widget.widgets.push(atom3);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::Recruit {
+            let mut atom1 = AtomWidget::new(vec!["Party Size".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("size".to_string(), 0));
+            atom1.atom_data.text = "Party Size".to_string();
+            let id = (behavior_data.id, node_data.id, "size".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "4".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::Dismiss {
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::Follow {
+            let mut atom1 = AtomWidget::new(vec!["Distance".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("distance".to_string(), 0));
+            atom1.atom_data.text = "Distance".to_string();
+            let id = (behavior_data.id, node_data.id, "distance".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "2".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec!["Speed".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("speed".to_string(), 0));
+            atom2.atom_data.text = "Speed".to_string();
+            let id = (behavior_data.id, node_data.id, "speed".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "8".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::Wander {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("area".to_string(), 0));
+            atom1.atom_data.text = "Area".to_string();
+            let id = (behavior_data.id, node_data.id, "area".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec!["Radius".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("radius".to_string(), 0));
+            atom2.atom_data.text = "Radius".to_string();
+            let id = (behavior_data.id, node_data.id, "radius".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "5".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
+            let mut atom3 = AtomWidget::new(vec!["Speed".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("speed".to_string(), 0));
+            atom3.atom_data.text = "Speed".to_string();
+            let id = (behavior_data.id, node_data.id, "speed".to_string());
+            atom3.behavior_id = Some(id.clone());
+            atom3.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "8".to_string()), self.graph_type);
+            node_widget.widgets.push(atom3);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::Flee {
+            let mut atom1 = AtomWidget::new(vec!["Distance".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("distance".to_string(), 0));
+            atom1.atom_data.text = "Distance".to_string();
+            let id = (behavior_data.id, node_data.id, "distance".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "6".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec!["Speed".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("speed".to_string(), 0));
+            atom2.atom_data.text = "Speed".to_string();
+            let id = (behavior_data.id, node_data.id, "speed".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "8".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
             node_widget.color = context.color_blue.clone();
             node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
         } else
+        if node_data.behavior_type == BehaviorNodeType::Patrol {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("waypoints".to_string(), 0));
+            atom1.atom_data.text = "Waypoints".to_string();
+            let id = (behavior_data.id, node_data.id, "waypoints".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "// Area or X Y per line".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec!["Speed".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("speed".to_string(), 0));
+            atom2.atom_data.text = "Speed".to_string();
+            let id = (behavior_data.id, node_data.id, "speed".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "8".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::AdjustReputation {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("faction".to_string(), 0));
+            atom1.atom_data.text = "Faction".to_string();
+            let id = (behavior_data.id, node_data.id, "faction".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec!["Amount".to_string()], AtomWidgetType::NodeExpressionValueButton,
+            AtomData::new_as_int("amount".to_string(), 0));
+            atom2.atom_data.text = "Amount".to_string();
+            let id = (behavior_data.id, node_data.id, "amount".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "-10".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom, NodeConnector { rect: (0,0,0,0) } );
+        } else
         if node_data.behavior_type == BehaviorNodeType::CallSystem {
 
             let mut atom1 = AtomWidget::new(context.data.systems_names.clone(), AtomWidgetType::NodeTextButton,
@@ -1521,6 +1960,40 @@ impl EditorContent for NodeGraph  {
             node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
         } else
+        if node_data.behavior_type == BehaviorNodeType::Quest {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("description".to_string(), 0));
+            atom1.atom_data.text = "Description".to_string();
+            let id = (behavior_data.id, node_data.id, "description".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            let mut atom2 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("stages".to_string(), 0));
+            atom2.atom_data.text = "Stages".to_string();
+            let id = (behavior_data.id, node_data.id, "stages".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "// Objective (talk Name, area Name, kill Count Behavior, item Name or none), Description\ntalk Smith, Talk to the smith".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
+            node_widget.color = context.color_orange.clone();
+        } else
+        if node_data.behavior_type == BehaviorNodeType::StartQuest || node_data.behavior_type == BehaviorNodeType::AdvanceQuest || node_data.behavior_type == BehaviorNodeType::CompleteQuest {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("quest".to_string(), 0));
+            atom1.atom_data.text = "Quest".to_string();
+            let id = (behavior_data.id, node_data.id, "quest".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
         if node_data.behavior_type == BehaviorNodeType::LockTree {
             let mut atom1 = AtomWidget::new(vec!["Self".to_string(), "Target".to_string()], AtomWidgetType::NodeMenuButton,
             AtomData::new_as_int("execute_for".to_string(), 0));
@@ -1581,6 +2054,49 @@ impl EditorContent for NodeGraph  {
             node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
             node_widget.node_connector.insert(BehaviorNodeConnector::Bottom, NodeConnector { rect: (0,0,0,0) } );
         } else
+        if node_data.behavior_type == BehaviorNodeType::Dialog {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("text".to_string(), 0));
+            atom1.atom_data.text = "Text".to_string();
+            let id = (behavior_data.id, node_data.id, "text".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "Hello ${Target}.".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            for index in 1..=4 {
+                let key = format!("choice{}", index);
+                let mut atom = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+                AtomData::new_as_int(key.clone(), 0));
+                atom.atom_data.text = format!("Choice {}", index);
+                let id = (behavior_data.id, node_data.id, key);
+                atom.behavior_id = Some(id.clone());
+                atom.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+                node_widget.widgets.push(atom);
+
+                let key = format!("condition{}", index);
+                let mut atom = AtomWidget::new(vec![format!("Condition {}", index)], AtomWidgetType::NodeExpressionButton,
+                AtomData::new_as_int(key.clone(), 0));
+                atom.atom_data.text = format!("Condition {}", index);
+                let id = (behavior_data.id, node_data.id, key);
+                atom.behavior_id = Some(id.clone());
+                atom.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+                node_widget.widgets.push(atom);
+            }
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom1, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom2, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom3, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Bottom4, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::Take || node_data.behavior_type == BehaviorNodeType::Drop || node_data.behavior_type == BehaviorNodeType::UseItem || node_data.behavior_type == BehaviorNodeType::Talk {
+            node_widget.color = context.color_gray.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Top, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Success, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Fail, NodeConnector { rect: (0,0,0,0) } );
+        } else
         if node_data.behavior_type == BehaviorNodeType::Move {
             let mut atom1 = AtomWidget::new(vec!["Speed".to_string()], AtomWidgetType::NodeExpressionValueButton,
             AtomData::new_as_int("speed".to_string(), 0));
@@ -1598,6 +2114,14 @@ impl EditorContent for NodeGraph  {
 
         // Area
         if node_data.behavior_type == BehaviorNodeType::InsideArea {
+            let mut atom1 = AtomWidget::new(vec!["Everyone".to_string(), "Players".to_string(), "NPCs".to_string()], AtomWidgetType::NodeMenuButton,
+            AtomData::new_as_int("applies_to".to_string(), 0));
+            atom1.atom_data.text = "Applies To".to_string();
+            let id = (behavior_data.id, node_data.id, "applies_to".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.curr_index = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type).0 as usize;
+            node_widget.widgets.push(atom1);
+
             node_widget.color = context.color_green.clone();
             node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
         } else
@@ -1614,6 +2138,14 @@ impl EditorContent for NodeGraph  {
             atom1.curr_index = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type).0 as usize;
             node_widget.widgets.push(atom1);
 
+            let mut atom2 = AtomWidget::new(vec!["Everyone".to_string(), "Players".to_string(), "NPCs".to_string()], AtomWidgetType::NodeMenuButton,
+            AtomData::new_as_int("applies_to".to_string(), 0));
+            atom2.atom_data.text = "Applies To".to_string();
+            let id = (behavior_data.id, node_data.id, "applies_to".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.curr_index = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type).0 as usize;
+            node_widget.widgets.push(atom2);
+
             node_widget.color = context.color_green.clone();
             node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
         } else
@@ -1677,6 +2209,45 @@ impl EditorContent for NodeGraph  {
         if node_data.behavior_type == BehaviorNodeType::LightArea {
             node_widget.color = context.color_blue.clone();
             node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+        } else
+        if node_data.behavior_type == BehaviorNodeType::Spawn {
+            let mut atom1 = AtomWidget::new(vec![], AtomWidgetType::NodeTextButton,
+            AtomData::new_as_int("behavior".to_string(), 0));
+            atom1.atom_data.text = "Behavior".to_string();
+            let id = (behavior_data.id, node_data.id, "behavior".to_string());
+            atom1.behavior_id = Some(id.clone());
+            atom1.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+            node_widget.widgets.push(atom1);
+
+            // Position, if not set the instances spawn on a random tile inside the area
+            let mut position_atom = AtomWidget::new(vec![], AtomWidgetType::NodePositionButton,
+            AtomData::new_as_int("position".to_string(), 0));
+            position_atom.atom_data.text = "Position".to_string();
+            let id = (behavior_data.id, node_data.id, "position".to_string());
+            position_atom.behavior_id = Some(id.clone());
+            position_atom.atom_data.data = context.data.get_behavior_id_value(id, (-1.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+            node_widget.widgets.push(position_atom);
+
+            let mut atom2 = AtomWidget::new(vec!["Maximum".to_string()], AtomWidgetType::NodeNumberButton,
+            AtomData::new_as_int("maximum".to_string(), 0));
+            atom2.atom_data.text = "Maximum".to_string();
+            let id = (behavior_data.id, node_data.id, "maximum".to_string());
+            atom2.behavior_id = Some(id.clone());
+            atom2.atom_data.data = context.data.get_behavior_id_value(id, (1.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+            node_widget.widgets.push(atom2);
+
+            // Respawn delay in ticks
+            let mut atom3 = AtomWidget::new(vec!["Delay".to_string()], AtomWidgetType::NodeNumberButton,
+            AtomData::new_as_int("delay".to_string(), 0));
+            atom3.atom_data.text = "Delay".to_string();
+            let id = (behavior_data.id, node_data.id, "delay".to_string());
+            atom3.behavior_id = Some(id.clone());
+            atom3.atom_data.data = context.data.get_behavior_id_value(id, (0.0,0.0,0.0,0.0, "".to_string()), self.graph_type);
+            node_widget.widgets.push(atom3);
+
+            node_widget.color = context.color_blue.clone();
+            node_widget.node_connector.insert(BehaviorNodeConnector::Left, NodeConnector { rect: (0,0,0,0) } );
+            node_widget.node_connector.insert(BehaviorNodeConnector::Right, NodeConnector { rect: (0,0,0,0) } );
         }
 
         //
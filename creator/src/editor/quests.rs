@@ -0,0 +1,92 @@
+use core_shared::asset::Asset;
+use core_server::gamedata::behavior::{ BehaviorInstanceType, BehaviorInstanceState };
+use core_server::gamedata::nodes_quest::get_quest;
+use crate::editor::ScreenContext;
+
+/// Shows the quest state values of the players while the game is running
+pub struct QuestsWidget {
+    pub rect                    : (isize, isize, usize, usize),
+
+    pub dirty                   : bool,
+    pub buffer                  : Vec<u8>,
+
+    pub size                    : (usize, usize),
+
+    pub drawn_lines             : Vec<String>,
+}
+
+impl QuestsWidget {
+
+    pub fn new(_context: &ScreenContext) -> Self {
+
+        Self {
+            rect                : (950, 450, 280, 200),
+
+            dirty               : true,
+            buffer              : vec![],
+
+            size                : (280, 200),
+
+            drawn_lines         : vec![],
+        }
+    }
+
+    pub fn draw(&mut self, _frame: &mut [u8], _anim_counter: usize, asset: &mut Asset, context: &mut ScreenContext) {
+
+        if self.buffer.len() != self.size.0 * self.size.1 * 4 {
+            self.buffer = vec![0;self.size.0 * self.size.1 * 4];
+        }
+
+        let lines = self.get_lines(context);
+        if lines != self.drawn_lines {
+            self.dirty = true;
+        }
+
+        let rect = (0, 0, self.size.0, self.size.1);
+
+        if self.dirty {
+            for i in &mut self.buffer[..] { *i = 0 }
+            let buffer_frame = &mut self.buffer[..];
+            let stride = self.size.0;
+
+            let b = context.color_black;
+            let w = context.color_white;
+
+            context.draw2d.draw_rounded_rect(buffer_frame, &rect, stride, &((rect.2 - 1) as f64, (rect.3 - 2) as f64), &[b[0], b[1], b[2], 150], &(20.0, 20.0, 20.0, 20.0));
+
+            let text_size = 13_usize;
+            let max_lines = (self.size.1 - 20) / text_size;
+
+            for (l, line) in lines.iter().take(max_lines).enumerate() {
+                context.draw2d.draw_text_rect(buffer_frame, &(15, 10 + l * text_size, self.size.0 - 30, text_size), rect.2, &asset.get_editor_font("OpenSans"), text_size as f32, line.as_str(), &[w[0], w[1], w[2], 150], &[b[0], b[1], b[2], 150], crate::draw2d::TextAlignment::Left);
+            }
+
+            self.drawn_lines = lines;
+        }
+        self.dirty = false;
+    }
+
+    /// One line per quest of every player, created from the "quest:" state values of the player instances
+    fn get_lines(&self, context: &ScreenContext) -> Vec<String> {
+        let mut lines = vec!["Quests".to_string()];
+        for instance in &context.data.instances {
+            if instance.instance_type != BehaviorInstanceType::Player || instance.state == BehaviorInstanceState::Purged {
+                continue;
+            }
+            let mut keys : Vec<&String> = instance.state_values.keys().filter(|key| key.starts_with("quest:")).collect();
+            keys.sort();
+
+            for key in keys {
+                let value = &instance.state_values[key];
+                let name = &key[6..];
+                if value.2 == 1.0 {
+                    lines.push(format!("{}: {}, completed", instance.name, name));
+                } else {
+                    let stages = get_quest(name, &context.data).map(|quest| quest.stages.len()).unwrap_or(0);
+                    lines.push(format!("{}: {}, stage {} of {}, progress {}", instance.name, name, value.0 as usize + 1, stages, value.1));
+                }
+            }
+        }
+        lines
+    }
+}
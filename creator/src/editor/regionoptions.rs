@@ -3,6 +3,9 @@ use crate::widget::codeeditor::CodeEditorMode;
 use core_server::gamedata::region::generate_region_sink_descriptions;
 use core_shared::asset::Asset;
 use core_shared::asset::TileUsage;
+use core_shared::regiondata::RegionInstance;
+use core_server::gamedata::behavior::BehaviorNodeType;
+use itertools::Itertools;
 
 use crate::widget::atom::AtomWidget;
 use crate::widget::atom::AtomWidgetType;
@@ -19,6 +22,7 @@ pub enum RegionEditorMode {
     Tiles,
     Areas,
     Behavior,
+    Characters,
     Settings
 }
 
@@ -31,6 +35,7 @@ pub struct RegionOptions {
     pub tile_widgets        : Vec<AtomWidget>,
     pub area_widgets        : Vec<AtomWidget>,
     pub behavior_widgets    : Vec<AtomWidget>,
+    pub character_widgets   : Vec<AtomWidget>,
 }
 
 impl EditorOptions for RegionOptions {
@@ -43,7 +48,7 @@ impl EditorOptions for RegionOptions {
     AtomData::new_as_int("Mode".to_string(), 0));
         mode_button.drag_enabled = true;
 
-        mode_button.add_group_list([50, 50, 50, 255], [80, 80, 80, 255], vec!["Draw Tiles".to_string(), "Edit Areas".to_string(), "Area Behavior".to_string(), "Settings".to_string()]);
+        mode_button.add_group_list([50, 50, 50, 255], [80, 80, 80, 255], vec!["Draw Tiles".to_string(), "Edit Areas".to_string(), "Area Behavior".to_string(), "Characters".to_string(), "Settings".to_string()]);
         mode_button.set_rect((rect.0, rect.1 + 10, rect.2, 200), asset, context);
         mode_button.centered_text = true;
         widgets.push(mode_button);
@@ -128,11 +133,33 @@ impl EditorOptions for RegionOptions {
 
         node_list.add_group_list(context.color_green, context.color_light_green, vec!["Always".to_string(), "Enter Area".to_string(), "Leave Area".to_string(), "Inside Area".to_string()]);
 
-        node_list.add_group_list(context.color_blue, context.color_light_blue, vec!["Audio".to_string(), "Displace Tiles".to_string(), "Light".to_string(), "Message".to_string(), "Spawn".to_string(), "Teleport".to_string()]);
+        node_list.add_group_list(context.color_blue, context.color_light_blue, vec!["Audio".to_string(), "Displace Tiles".to_string(), "Emit Event".to_string(), "Expression".to_string(), "Light".to_string(), "Message".to_string(), "Script".to_string(), "Spawn".to_string(), "Teleport".to_string(), "Time of Day".to_string()]);
 
         node_list.set_rect((rect.0 + 10, rect.1 + 200, rect.2 - 20, rect.3 - 200), asset, context);
         behavior_widgets.push(node_list);
 
+        // Characters
+
+        let mut character_widgets : Vec<AtomWidget> = vec![];
+
+        let mut characters_button = AtomWidget::new(vec![], AtomWidgetType::SliderButton,
+        AtomData::new_as_int("Character".to_string(), 0));
+        characters_button.atom_data.text = "Character".to_string();
+        characters_button.set_rect((rect.0 + 10, rect.1 + 150, rect.2 - 20, 40), asset, context);
+        character_widgets.push(characters_button);
+
+        let mut del_instance_button = AtomWidget::new(vec!["Delete".to_string()], AtomWidgetType::Button,
+            AtomData::new_as_int("Delete".to_string(), 0));
+        del_instance_button.state = WidgetState::Disabled;
+        del_instance_button.set_rect((rect.0 + 10, rect.1 + 200, rect.2 - 20, 40), asset, context);
+        character_widgets.push(del_instance_button);
+
+        let mut variables_button = AtomWidget::new(vec!["Variables".to_string()], AtomWidgetType::Button,
+            AtomData::new_as_int("Variables".to_string(), 0));
+        variables_button.state = WidgetState::Disabled;
+        variables_button.set_rect((rect.0 + 10, rect.1 + 235, rect.2 - 20, 40), asset, context);
+        character_widgets.push(variables_button);
+
         Self {
             rect,
             widgets,
@@ -142,6 +169,7 @@ impl EditorOptions for RegionOptions {
             tile_widgets,
             area_widgets,
             behavior_widgets,
+            character_widgets,
         }
     }
 
@@ -174,6 +202,11 @@ impl EditorOptions for RegionOptions {
             for atom in &mut self.behavior_widgets {
                 atom.draw(frame, context.width, anim_counter, asset, context);
             }
+        } else
+        if mode == RegionEditorMode::Characters {
+            for atom in &mut self.character_widgets {
+                atom.draw(frame, context.width, anim_counter, asset, context);
+            }
         }
 
         if mode == RegionEditorMode::Tiles {
@@ -201,7 +234,7 @@ impl EditorOptions for RegionOptions {
 
                 if atom.atom_data.id == "Mode" {
 
-                    if atom.curr_item_index == 3 {
+                    if atom.curr_item_index == 4 {
                         if let Some(content) = content {
                             if let Some(region) = context.data.regions.get_mut(&content.get_region_id()) {
 
@@ -260,6 +293,13 @@ impl EditorOptions for RegionOptions {
                     return true;
                 }
             }
+        } else
+        if mode == RegionEditorMode::Characters {
+            for atom in &mut self.character_widgets {
+                if atom.mouse_down(pos, asset, context) {
+                    return true;
+                }
+            }
         }
 
         false
@@ -364,6 +404,60 @@ impl EditorOptions for RegionOptions {
             for atom in &mut self.behavior_widgets {
                 if atom.mouse_up(pos, asset, context) {
 
+                    return true;
+                }
+            }
+        } else
+        if mode == RegionEditorMode::Characters {
+            if mode_was_updated {
+                if let Some(el_content) = content {
+                    self.update_character_ui(el_content.get_region_id(), context);
+                }
+            }
+            for atom in &mut self.character_widgets {
+                if atom.mouse_up(pos, asset, context) {
+
+                    if atom.atom_data.id == "Delete" {
+                        if let Some(el_content) = content {
+                            if let Some(region) = context.data.regions.get_mut(&el_content.get_region_id()) {
+                                if let Some(index) = context.curr_region_instance_index {
+                                    if index < region.data.instances.len() {
+                                        region.data.instances.remove(index);
+                                        region.save_data();
+                                    }
+                                }
+                            }
+                        }
+                        context.curr_region_instance_index = None;
+                        if let Some(el_content) = content {
+                            self.update_character_ui(el_content.get_region_id(), context);
+                        }
+                    } else
+                    if atom.atom_data.id == "Variables" {
+                        if let Some(el_content) = content {
+                            if let Some(region) = context.data.regions.get(&el_content.get_region_id()) {
+                                if let Some(index) = context.curr_region_instance_index {
+                                    if let Some(instance) = region.data.instances.get(index) {
+                                        context.code_editor_is_active = true;
+                                        context.code_editor_just_opened = true;
+                                        context.code_editor_mode = CodeEditorMode::Settings;
+                                        let mut defaults = vec![];
+                                        if let Some(behavior) = context.data.behaviors.get(&instance.behavior) {
+                                            for (_id, node) in behavior.data.nodes.iter().sorted_by_key(|(id, _)| **id) {
+                                                if node.behavior_type == BehaviorNodeType::VariableNumber {
+                                                    let value = if let Some(value) = node.values.get("value") { value.0 } else { 0.0 };
+                                                    defaults.push((node.name.clone(), value));
+                                                }
+                                            }
+                                        }
+                                        context.code_editor_node_behavior_value.4 = instance_variables_to_string(instance, defaults);
+                                        context.code_editor_node_behavior_id.0 = 130001;
+                                    }
+                                }
+                            }
+                        }
+                    }
+
                     return true;
                 }
             }
@@ -394,6 +488,13 @@ impl EditorOptions for RegionOptions {
                 }
             }
         } else
+        if mode == RegionEditorMode::Characters {
+            for atom in &mut self.character_widgets {
+                if atom.mouse_hover(pos, asset, context) {
+                    return true;
+                }
+            }
+        } else
         if mode == RegionEditorMode::Behavior {
             self.area_widgets[0].mouse_hover(pos, asset, context);
             for atom in &mut self.behavior_widgets {
@@ -452,7 +553,8 @@ impl EditorOptions for RegionOptions {
         match mode {
             1 => RegionEditorMode::Areas,
             2 => RegionEditorMode::Behavior,
-            3 => RegionEditorMode::Settings,
+            3 => RegionEditorMode::Characters,
+            4 => RegionEditorMode::Settings,
             _ => RegionEditorMode::Tiles
         }
     }
@@ -490,6 +592,45 @@ impl EditorOptions for RegionOptions {
         }
     }
 
+    /// Update the character ui
+    fn update_character_ui(&mut self, region_id: usize, context: &mut ScreenContext) {
+
+        self.character_widgets[0].text = context.data.behaviors_names.iter().filter(|name| *name != "Player").cloned().collect();
+        if self.character_widgets[0].curr_index >= self.character_widgets[0].text.len() {
+            self.character_widgets[0].curr_index = 0;
+        }
+
+        let mut has_selection = false;
+        if let Some(region) = context.data.regions.get(&region_id) {
+            if let Some(index) = context.curr_region_instance_index {
+                has_selection = index < region.data.instances.len();
+            }
+        }
+
+        if has_selection {
+            self.character_widgets[1].state = WidgetState::Normal;
+            self.character_widgets[2].state = WidgetState::Normal;
+        } else {
+            context.curr_region_instance_index = None;
+            self.character_widgets[1].state = WidgetState::Disabled;
+            self.character_widgets[2].state = WidgetState::Disabled;
+        }
+
+        for a in &mut self.character_widgets {
+            a.dirty = true;
+        }
+    }
+
+    /// Returns the behavior id of the character to place
+    fn get_character_behavior_id(&self, context: &ScreenContext) -> Option<usize> {
+        if let Some(name) = self.character_widgets[0].text.get(self.character_widgets[0].curr_index) {
+            if let Some(index) = context.data.behaviors_names.iter().position(|n| n == name) {
+                return Some(context.data.behaviors_ids[index]);
+            }
+        }
+        None
+    }
+
     /// Sets a new name for the current area
     fn set_area_name(&mut self, name: String, context: &mut ScreenContext, content: &mut Option<Box<dyn EditorContent>>) {
 
@@ -551,4 +692,23 @@ impl EditorOptions for RegionOptions {
         self.area_widgets[0].text = names;
         self.area_widgets[0].dirty = true;
     }
-}
\ No newline at end of file
+}
+
+/// The variables of a placed instance in the settings format, variables which are not overridden are commented out
+pub fn instance_variables_to_string(instance: &RegionInstance, defaults: Vec<(String, f64)>) -> String {
+    let mut string = "// The name of the character\n".to_string();
+    if let Some(name) = &instance.name {
+        string += format!("name = \"{}\"\n", name).as_str();
+    } else {
+        string += "// name = \"\"\n";
+    }
+    string += "// The number variables of the character\n";
+    for (name, value) in defaults {
+        if let Some((_, value)) = instance.number_values.iter().find(|(n, _)| *n == name) {
+            string += format!("{} = {}\n", name, value).as_str();
+        } else {
+            string += format!("// {} = {}\n", name, value).as_str();
+        }
+    }
+    string
+}
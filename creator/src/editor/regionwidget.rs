@@ -1,6 +1,7 @@
 use core_server::gamedata::region::generate_region_sink_descriptions;
 use core_shared::asset::{ Asset };
 use core_shared::asset::tileset::TileUsage;
+use core_shared::regiondata::RegionInstance;
 
 use crate::widget::codeeditor::CodeEditorMode;
 use crate::widget::context::ScreenContext;
@@ -82,7 +83,7 @@ impl EditorContent for RegionWidget {
             let editor_mode = options.get_editor_mode();
 
             let mut rect = self.rect.clone();
-            if editor_mode != RegionEditorMode::Areas {
+            if editor_mode != RegionEditorMode::Areas && editor_mode != RegionEditorMode::Characters {
                 rect.3 -= 250;
             }
             let grid_size = self.grid_size;
@@ -155,6 +156,32 @@ impl EditorContent for RegionWidget {
                 }
             }
 
+            if editor_mode == RegionEditorMode::Characters {
+                if let Some(region) = context.data.regions.get(&self.region_id) {
+
+                    let x_tiles = (rect.2 / grid_size) as isize;
+                    let y_tiles = (rect.3 / grid_size) as isize;
+
+                    for (index, instance) in region.data.instances.iter().enumerate() {
+                        let x = instance.position.0 + self.offset.0;
+                        let y = instance.position.1 + self.offset.1;
+
+                        if x >= 0 && y >= 0 && x < x_tiles && y < y_tiles {
+                            let pos = (rect.0 + left_offset + (x as usize) * grid_size, rect.1 + top_offset + (y as usize) * grid_size);
+
+                            if let Some(tile) = context.data.get_behavior_default_tile(instance.behavior) {
+                                let map = asset.get_map_of_id(tile.0);
+                                context.draw2d.draw_animated_tile(frame, &pos, map, context.width, &(tile.1, tile.2), anim_counter, grid_size);
+                            }
+
+                            if context.curr_region_instance_index == Some(index) {
+                                context.draw2d.draw_rect_outline(frame, &(pos.0, pos.1, grid_size, grid_size), context.width, context.color_white);
+                            }
+                        }
+                    }
+                }
+            }
+
             if editor_mode == RegionEditorMode::Behavior {
                 self.behavior_graph.draw(frame, anim_counter, asset, context, &mut None);
             }
@@ -208,6 +235,21 @@ impl EditorContent for RegionWidget {
                             }
                         }
                     } else
+                    if editor_mode == RegionEditorMode::Characters {
+                        let behavior_id = options.get_character_behavior_id(context);
+                        if let Some(region) = context.data.regions.get_mut(&self.region_id) {
+                            // Select the character at the position or place a new one
+                            if let Some(index) = region.data.instances.iter().position(|instance| instance.position == id) {
+                                context.curr_region_instance_index = Some(index);
+                            } else
+                            if let Some(behavior_id) = behavior_id {
+                                region.data.instances.push(RegionInstance { behavior: behavior_id, position: id, name: None, number_values: vec![], faction: None });
+                                context.curr_region_instance_index = Some(region.data.instances.len() - 1);
+                                region.save_data();
+                            }
+                        }
+                        options.update_character_ui(self.region_id, context);
+                    } else
                     if editor_mode == RegionEditorMode::Areas {
                         if let Some(region) = context.data.regions.get_mut(&self.region_id) {
                             if region.data.areas.len() > 0 {
@@ -335,6 +377,11 @@ impl EditorContent for RegionWidget {
             }
         }
 
+        if let Some(options) = options {
+            context.curr_region_instance_index = None;
+            options.update_character_ui(id, context);
+        }
+
         if let Some(options) = options {
             let mode = options.get_editor_mode();
             if mode == RegionEditorMode::Settings {
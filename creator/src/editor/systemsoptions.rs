@@ -27,11 +27,11 @@ impl EditorOptions for SystemsOptions {
     AtomData::new_as_int("NodeList".to_string(), 0));
         node_list.drag_enabled = true;
 
-        node_list.add_group_list(context.color_green, context.color_light_green, vec!["Behavior Tree".to_string(), "Expression".to_string(), "Script".to_string(), "Linear".to_string(), "Sequence".to_string()]);
+        node_list.add_group_list(context.color_green, context.color_light_green, vec!["Behavior Tree".to_string(), "Expression".to_string(), "Script".to_string(), "Linear".to_string(), "Sequence".to_string(), "Selector".to_string(), "Inverter".to_string(), "Repeat".to_string(), "Parallel".to_string(), "Cooldown".to_string(), "Random".to_string(), "Time of Day".to_string()]);
 
         node_list.add_group_list(context.color_orange, context.color_light_orange, vec!["Number".to_string(),/* "Position".to_string()*/ ]);
 
-        node_list.add_group_list(context.color_blue, context.color_light_blue, vec![ "Close In".to_string(), "Lookout".to_string(), "Pathfinder".to_string(), "Call Behavior".to_string(), "Call System".to_string(), "Lock Tree".to_string(), "Unlock".to_string(), "Set State".to_string(), "Message".to_string() ]);
+        node_list.add_group_list(context.color_blue, context.color_light_blue, vec![ "Close In".to_string(), "Lookout".to_string(), "Pathfinder".to_string(), "Schedule".to_string(), "Follow".to_string(), "Wander".to_string(), "Flee".to_string(), "Patrol".to_string(), "Call Behavior".to_string(), "Call System".to_string(), "Lock Tree".to_string(), "Unlock".to_string(), "Set State".to_string(), "Message".to_string(), "Emit Event".to_string(), "Start Quest".to_string(), "Advance Quest".to_string(), "Complete Quest".to_string(), "Adjust Reputation".to_string(), "Recruit".to_string(), "Dismiss".to_string() ]);
 
         node_list.set_rect(rect, asset, context);
         widgets.push(node_list);
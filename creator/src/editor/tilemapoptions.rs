@@ -53,6 +53,13 @@ impl EditorOptions for TileMapOptions {
         set_default_button.set_rect((rect.0 + 10, rect.1 + 15 + 370 + 80, rect.2 - 20, 40), asset, context);
         widgets.push(set_default_button);
 
+        let mut movement_cost_button = AtomWidget::new(vec!["Default".to_string(), "1".to_string(), "2".to_string(), "3".to_string(), "4".to_string(), "5".to_string(), "6".to_string()], AtomWidgetType::SliderButton,
+        AtomData::new_as_int("Movement Cost".to_string(), 0));
+        movement_cost_button.atom_data.text = "Movement Cost".to_string();
+        movement_cost_button.state = WidgetState::Disabled;
+        movement_cost_button.set_rect((rect.0 + 10, rect.1 + 15 + 370 + 130, rect.2 - 20, 40), asset, context);
+        widgets.push(movement_cost_button);
+
         Self {
             rect,
             widgets             : widgets,
@@ -149,6 +156,10 @@ impl EditorOptions for TileMapOptions {
                     } else
                     if atom.atom_data.id == "Set Default" {
                         self.set_default_tile(asset, context);
+                    } else
+                    if atom.atom_data.id == "Movement Cost" {
+                        let cost = atom.curr_index;
+                        self.set_movement_cost(cost, asset, context);
                     }
                 }
                 return true;
@@ -205,12 +216,15 @@ impl EditorOptions for TileMapOptions {
                 TileUsage::UIElement => self.widgets[0].curr_item_index = 9,
             }
             self.widgets[1].text[0] = tile.tags;
+            self.widgets[5].curr_index = tile.movement_cost.min(self.widgets[5].text.len() - 1);
         } else {
             self.widgets[0].curr_item_index = 0;
             self.widgets[1].text[0] = "".to_string();
+            self.widgets[5].curr_index = 0;
         }
         self.widgets[0].dirty = true;
         self.widgets[1].dirty = true;
+        self.widgets[5].dirty = true;
     }
 
     /// Sets the tile anim for the current tile
@@ -306,4 +320,33 @@ impl EditorOptions for TileMapOptions {
             map.save_settings();
         }
     }
-}
\ No newline at end of file
+
+    /// Set the movement cost of the selected tiles, 0 uses the default of the tile usage
+    fn set_movement_cost(&mut self, cost: usize, asset: &mut Asset, context: &ScreenContext) {
+        if let Some(tile_id) = context.curr_tile {
+            if let Some(map)= asset.tileset.maps.get_mut(&asset.tileset.maps_ids[context.curr_tileset_index]) {
+                let mut i = tile_id.clone();
+                let mut tiles : Vec<(usize, usize)> = vec![i];
+
+                // Collect all tiles in the selection
+                if let Some(selection_end) = context.selection_end {
+                    while i.0 != selection_end.0 || i.1 != selection_end.1 {
+                        i.0 += 1;
+                        if i.0 >= map.max_tiles_per_row() {
+                            i.0 = 0;
+                            i.1 += 1;
+                        }
+                        tiles.push(i);
+                    }
+                }
+
+                for id in &tiles {
+                    let mut tile = map.get_tile(id);
+                    tile.movement_cost = cost;
+                    map.set_tile(*id, tile);
+                }
+                map.save_settings();
+            }
+        }
+    }
+}
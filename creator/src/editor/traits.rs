@@ -56,6 +56,9 @@ pub trait EditorOptions {
     /// Set the tile tags
     fn set_tags(&mut self, tags: String, asset: &mut Asset, context: &ScreenContext) {}
 
+    /// Set the movement cost of the selected tiles
+    fn set_movement_cost(&mut self, cost: usize, asset: &mut Asset, context: &ScreenContext) {}
+
     // For RegionOptions
 
     /// Returns the current region editor mode
@@ -67,6 +70,12 @@ pub trait EditorOptions {
     /// Sets a new name for the current area
     fn set_area_name(&mut self, name: String, context: &mut ScreenContext, content: &mut Option<Box<dyn EditorContent>>) {}
 
+    /// Update the ui of the character placement mode
+    fn update_character_ui(&mut self, region_id: usize, context: &mut ScreenContext) {}
+
+    /// The behavior id of the character to place
+    fn get_character_behavior_id(&self, context: &ScreenContext) -> Option<usize> { None }
+
     /// Get the current tile usage
     fn get_tile_usage(&self) -> TileUsage { TileUsage::Environment }
 
@@ -73,6 +73,7 @@ pub struct ScreenContext<'a> {
 
     pub curr_region_index               : usize,
     pub curr_region_area_index          : usize,
+    pub curr_region_instance_index      : Option<usize>,
     pub curr_region_tile                : Option<(usize, usize, usize, TileUsage)>,
 
     pub curr_behavior_index             : usize,
@@ -258,6 +259,7 @@ impl ScreenContext<'_> {
             // Regions / Areas
             curr_region_index           : 0,
             curr_region_area_index      : 0,
+            curr_region_instance_index  : None,
             curr_region_tile            : None,
 
             // Behaviors
@@ -0,0 +1,292 @@
+mod prelude {
+    pub const DEFAULT_TICK_IN_MS : u64 = 250;
+
+    /// If the server falls behind by more ticks than this we stop catching up and reset the schedule
+    pub const MAX_TICKS_BEHIND   : u32 = 5;
+}
+
+use core_server::gamedata::GameData;
+use core_server::gamedata::replay::Replay;
+use core_server::server::GameServer;
+use prelude::*;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// The command line options of the server
+struct Options {
+    path                        : PathBuf,
+    tick_in_ms                  : u64,
+    listen                      : Option<String>,
+    max_ticks                   : Option<u64>,
+    seed                        : Option<u64>,
+    record                      : Option<PathBuf>,
+    replay                      : Option<PathBuf>,
+    save_state                  : Option<PathBuf>,
+    compare                     : Option<PathBuf>,
+}
+
+fn print_usage() {
+    println!("Usage: server_standalone [PROJECT_PATH] [--tick-ms MS] [--listen ADDR] [--ticks COUNT] [--seed SEED]");
+    println!("                         [--record FILE] [--replay FILE] [--save-state FILE] [--compare FILE]");
+    println!("  PROJECT_PATH      Path of the game project, defaults to the current directory");
+    println!("  --tick-ms MS      Milliseconds per game tick, defaults to {}", DEFAULT_TICK_IN_MS);
+    println!("  --listen ADDR     Accept client connections on the given address, e.g. 0.0.0.0:7777");
+    println!("  --ticks COUNT     Stop after the given amount of ticks");
+    println!("  --seed SEED       Run deterministically with the given random seed");
+    println!("  --record FILE     Record the player input of the session into the given replay file");
+    println!("  --replay FILE     Replay the given session as fast as possible, then exit");
+    println!("  --save-state FILE Write the final game state to the given file");
+    println!("  --compare FILE    Compare the final game state with the given state file, exits with 1 on differences");
+}
+
+/// Parses the command line, returns None if the server should not start
+fn parse_options() -> Option<Options> {
+    let mut options = Options {
+        path                    : PathBuf::new(),
+        tick_in_ms              : DEFAULT_TICK_IN_MS,
+        listen                  : None,
+        max_ticks               : None,
+        seed                    : None,
+        record                  : None,
+        replay                  : None,
+        save_state              : None,
+        compare                 : None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tick-ms" => {
+                if let Some(ms) = args.next().and_then(|v| v.parse::<u64>().ok()).filter(|ms| *ms > 0) {
+                    options.tick_in_ms = ms;
+                } else {
+                    println!("--tick-ms requires a positive number");
+                    return None;
+                }
+            },
+            "--listen" => {
+                if let Some(addr) = args.next() {
+                    options.listen = Some(addr);
+                } else {
+                    println!("--listen requires an address");
+                    return None;
+                }
+            },
+            "--ticks" => {
+                if let Some(ticks) = args.next().and_then(|v| v.parse::<u64>().ok()) {
+                    options.max_ticks = Some(ticks);
+                } else {
+                    println!("--ticks requires a number");
+                    return None;
+                }
+            },
+            "--seed" => {
+                if let Some(seed) = args.next().and_then(|v| v.parse::<u64>().ok()) {
+                    options.seed = Some(seed);
+                } else {
+                    println!("--seed requires a number");
+                    return None;
+                }
+            },
+            "--record" | "--replay" | "--save-state" | "--compare" => {
+                if let Some(file) = args.next() {
+                    let file = Some(PathBuf::from(file));
+                    match arg.as_str() {
+                        "--record" => options.record = file,
+                        "--replay" => options.replay = file,
+                        "--save-state" => options.save_state = file,
+                        _ => options.compare = file,
+                    }
+                } else {
+                    println!("{} requires a file", arg);
+                    return None;
+                }
+            },
+            "--help" | "-h" => {
+                print_usage();
+                return None;
+            },
+            _ => {
+                if arg.starts_with("-") {
+                    println!("Unknown option {}", arg);
+                    print_usage();
+                    return None;
+                }
+                options.path = PathBuf::from(arg);
+            }
+        }
+    }
+
+    if options.replay.is_some() && (options.listen.is_some() || options.record.is_some()) {
+        println!("--replay can not be combined with --listen or --record");
+        return None;
+    }
+
+    Some(options)
+}
+
+/// Writes and / or compares the final game state, returns false if the state differs from the compared state
+fn check_final_state(game: &mut GameData, options: &Options) -> bool {
+    let state = game.save();
+
+    if let Some(path) = &options.save_state {
+        if std::fs::write(path, &state).is_err() {
+            println!("Failed to write the game state to {:?}", path);
+        }
+    }
+
+    if let Some(path) = &options.compare {
+        let expected = std::fs::read_to_string(path).ok().and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
+        let state = serde_json::from_str::<serde_json::Value>(&state).ok();
+
+        if let Some(expected) = expected {
+            if Some(&expected) == state.as_ref() {
+                println!("Final state matches {:?}", path);
+                return true;
+            }
+
+            // Name the instances which differ to make the report useful
+            if let Some(state) = state {
+                let empty = vec![];
+                let instances = state["instances"].as_array().unwrap_or(&empty);
+                let expected_instances = expected["instances"].as_array().unwrap_or(&empty);
+                for index in 0..instances.len().max(expected_instances.len()) {
+                    if instances.get(index) != expected_instances.get(index) {
+                        let name = instances.get(index).or(expected_instances.get(index)).map(|i| i["name"].to_string()).unwrap_or_default();
+                        println!("Instance {} ({}) differs", index, name);
+                    }
+                }
+            }
+            println!("Final state differs from {:?}", path);
+        } else {
+            println!("Failed to read the game state from {:?}", path);
+        }
+        return false;
+    }
+    true
+}
+
+fn main() {
+
+    env_logger::init();
+
+    let options = match parse_options() {
+        Some(options) => options,
+        None => return,
+    };
+
+    // Stop the tick loop on Ctrl-C / SIGTERM
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || running.store(false, Ordering::SeqCst)) {
+            println!("Failed to install the signal handler: {}", e);
+        }
+    }
+
+    // Init server
+    let mut game = GameData::load_from_path(options.path.clone());
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
+    }
+
+    // Headless replay of a recorded session
+    if let Some(path) = &options.replay {
+        if let Some(replay) = Replay::load_from_path(path) {
+            println!("Replaying {:?} ({} ticks)", path, replay.ticks);
+            game.start_replay(replay);
+            while game.replay_finished() == false && running.load(Ordering::SeqCst) {
+                game.tick();
+            }
+            let matches = check_final_state(&mut game, &options);
+            game.shutdown();
+            if matches == false {
+                std::process::exit(1);
+            }
+        } else {
+            println!("Failed to load the replay {:?}", path);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if options.record.is_some() {
+        game.start_recording(None);
+    }
+    game.startup_server();
+
+
+    let mut server = None;
+    if let Some(addr) = &options.listen {
+        match GameServer::bind(addr) {
+            Ok(s) => {
+                if let Some(addr) = s.local_addr().ok() {
+                    println!("Listening on {}", addr);
+                }
+                // Clients get delta updates over the network
+                game.delta_updates = true;
+                server = Some(s);
+            },
+            Err(e) => {
+                println!("Failed to listen on {}: {}", addr, e);
+                game.shutdown();
+                return;
+            }
+        }
+    }
+
+    println!("Server started, ticking every {}ms", options.tick_in_ms);
+
+    let tick_duration = Duration::from_millis(options.tick_in_ms);
+    let mut next_tick = Instant::now();
+    let mut ticks : u64 = 0;
+
+    while running.load(Ordering::SeqCst) {
+
+        if let Some(max_ticks) = options.max_ticks {
+            if ticks >= max_ticks {
+                break;
+            }
+        }
+
+        if let Some(server) = &mut server {
+            server.tick(&mut game);
+        } else {
+            game.tick();
+        }
+        ticks += 1;
+
+        // Schedule the next tick relative to the previous schedule, not to now, so that the tick
+        // duration does not accumulate as drift
+        next_tick += tick_duration;
+        let now = Instant::now();
+        if next_tick > now {
+            std::thread::sleep(next_tick - now);
+        } else
+        if now - next_tick > tick_duration * MAX_TICKS_BEHIND {
+            println!("Server is running {}ms behind, skipping ticks", (now - next_tick).as_millis());
+            next_tick = now;
+        }
+    }
+
+    println!("Shutting down after {} ticks", ticks);
+
+    if let Some(path) = &options.record {
+        if let Some(replay) = game.stop_recording() {
+            if replay.save_to_path(path) {
+                println!("Recorded {} events into {:?}", replay.events.len(), path);
+            } else {
+                println!("Failed to write the replay {:?}", path);
+            }
+        }
+    }
+
+    let matches = check_final_state(&mut game, &options);
+    game.shutdown();
+    if matches == false {
+        std::process::exit(1);
+    }
+}